@@ -1,13 +1,6 @@
-// ============== GAME CONSTANTS ==============
-
-/// Maximum turns a player can hold
-pub const MAX_TURNS: u8 = 9;
-
-/// Starting turns when joining a round
-pub const STARTING_TURNS: u8 = 3;
+use anchor_lang::prelude::*;
 
-/// Turns regenerated per hour
-pub const TURNS_PER_HOUR: u8 = 3;
+// ============== GAME CONSTANTS ==============
 
 /// Turn regeneration interval in seconds (1 hour)
 pub const TURN_REGEN_INTERVAL: i64 = 3600;
@@ -28,52 +21,88 @@ pub const LATE_JOIN_FEE_TIER2: u64 = 15000; // 150%
 pub const LATE_JOIN_ATTR_PENALTY_TIER1: u16 = 1000; // 10%
 pub const LATE_JOIN_ATTR_PENALTY_TIER2: u16 = 2000; // 20%
 
+/// Maximum rerolls per player per round
+pub const MAX_REROLLS: usize = 3;
+
+/// Minimum reroll fee as a fraction of one whole token (100 = 0.01 token),
+/// so the fee scales with the mint's decimals
+pub const MIN_REROLL_FEE_DIVISOR: u64 = 100;
+
+/// Decimals used to validate prices of native SOL rounds (lamports)
+pub const NATIVE_SOL_DECIMALS: u8 = 9;
+
+/// Domain-separation prefixes for allowlist Merkle leaves and inner nodes
+pub const ALLOWLIST_LEAF_PREFIX: &[u8] = &[0];
+pub const ALLOWLIST_NODE_PREFIX: &[u8] = &[1];
+
+/// Maximum protocol rake in basis points (20%)
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 2000;
+
+/// Default ephemeral-rollup validator and commit frequency for new deployments
+pub const DEFAULT_ER_VALIDATOR: Pubkey = pubkey!("MAS1Dt9qreoRMQ14YQuhg8UTZMMzDdKhmkZMECCzk57");
+pub const DEFAULT_COMMIT_FREQUENCY_MS: u32 = 30_000;
+
 /// Turn price multipliers for progressive pricing (in basis points)
 pub const TURN_PRICE_MULTIPLIER_2: u64 = 15000; // 150% for 2nd turn
 pub const TURN_PRICE_MULTIPLIER_3: u64 = 20000; // 200% for 3rd+ turn
 
 /// Seeds for PDA derivation
 pub const GLOBAL_CONFIG_SEED: &[u8] = b"global_config";
+pub const ARENA_SEED: &[u8] = b"arena";
 pub const GAME_ROUND_SEED: &[u8] = b"game_round";
 pub const PLAYER_STATE_SEED: &[u8] = b"player_state";
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const LEADERBOARD_SEED: &[u8] = b"leaderboard";
+pub const ROUND_RULES_SEED: &[u8] = b"round_rules";
+pub const ROUND_TEMPLATE_SEED: &[u8] = b"round_template";
+pub const ROLLOVER_VAULT_SEED: &[u8] = b"rollover_vault";
+pub const SOL_VAULT_SEED: &[u8] = b"sol_vault";
+pub const SPONSOR_SEED: &[u8] = b"sponsor";
+pub const PENDING_DEPOSITS_SEED: &[u8] = b"pending_deposits";
 
 // ============== LEVEL SYSTEM CONSTANTS ==============
 
-/// XP thresholds for each level (cumulative)
-/// Level 0->1: 5 XP, 1->2: 10 XP, 2->3: 15 XP, ..., 9->10: 50 XP
-/// Total to reach level 10: 275 XP
-pub const LEVEL_XP_THRESHOLDS: [u32; 11] = [
-    0,   // Level 0 (starting)
-    5,   // Level 1
-    15,  // Level 2 (5 + 10)
-    30,  // Level 3 (15 + 15)
-    50,  // Level 4 (30 + 20)
-    75,  // Level 5 (50 + 25)
-    105, // Level 6 (75 + 30)
-    140, // Level 7 (105 + 35)
-    180, // Level 8 (140 + 40)
-    225, // Level 9 (180 + 45)
-    275, // Level 10 (225 + 50)
-];
-
-/// XP gained per win
-pub const XP_PER_WIN: u32 = 3;
-
-/// Maximum level
+/// Maximum level (XP thresholds are set per round in `RoundRules`)
 pub const MAX_LEVEL: u8 = 10;
 
-/// Maximum attribute cap (base 5-15 + 5 from levels = 20 max)
-pub const MAX_ATTRIBUTE: u8 = 20;
-
 // ============== BATTLE CONSTANTS ==============
 
-/// Maximum battle turns
-pub const MAX_BATTLE_TURNS: u8 = 10;
-
 /// Base HP constant
 pub const BASE_HP: u16 = 100;
 
-/// Crit damage multiplier (50% = 0.5, stored as basis points)
-pub const CRIT_DAMAGE_MULTIPLIER: u16 = 5000; // 50% in basis points (10000 = 100%)
+// ============== PAUSE FLAGS ==============
+
+/// Bits of `GlobalConfig::paused_actions`
+pub const PAUSE_JOIN: u8 = 1 << 0;
+pub const PAUSE_REROLL: u8 = 1 << 1;
+pub const PAUSE_BUY_PACKS: u8 = 1 << 2;
+pub const PAUSE_BATTLE: u8 = 1 << 3;
+pub const PAUSE_CLAIM: u8 = 1 << 4;
+pub const PAUSE_CRANKS: u8 = 1 << 5;
+pub const PAUSE_SPONSOR: u8 = 1 << 6;
+pub const PAUSE_ALL: u8 = PAUSE_JOIN
+    | PAUSE_REROLL
+    | PAUSE_BUY_PACKS
+    | PAUSE_BATTLE
+    | PAUSE_CLAIM
+    | PAUSE_CRANKS
+    | PAUSE_SPONSOR;
+
+// ============== ACCOUNT VERSIONS ==============
+
+/// Current layout versions, stored in each account's leading `version` byte
+pub const GLOBAL_CONFIG_VERSION: u8 = 1;
+pub const GAME_ROUND_VERSION: u8 = 1;
+pub const PLAYER_STATE_VERSION: u8 = 1;
+
+/// Sizes (without discriminator) of the baseline layouts that predate the `version` byte
+/// (single-arena GlobalConfig with `token_mint`/`current_round`; see `migrate` for the
+/// layouts and their upgrade to the current ones)
+pub const GLOBAL_CONFIG_V0_SPACE: usize = 82;
+pub const GAME_ROUND_V0_SPACE: usize = 108;
+pub const PLAYER_STATE_V0_SPACE: usize = 134;
+
+// ============== LEADERBOARD CONSTANTS ==============
+
+/// Maximum leaderboard size (account space is reserved for this many entries)
+pub const MAX_LEADERBOARD_SIZE: usize = 20;
@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 #[error_code]
 pub enum PandaBattleError {
     // Admin Errors
-    #[msg("Only the admin can perform this action")]
+    #[msg("Signer is not authorized to perform this action")]
     Unauthorized,
 
     #[msg("Invalid configuration parameter")]
@@ -91,4 +91,104 @@ pub enum PandaBattleError {
     // Token Errors
     #[msg("Invalid token mint for this round")]
     InvalidMint,
+
+    // Admin Errors (added later; new variants go at the end so existing codes stay stable)
+    #[msg("Round rules account does not match this round")]
+    InvalidRules,
+
+    #[msg("No admin handover is pending")]
+    NoPendingAdmin,
+
+    #[msg("This action is currently paused")]
+    Paused,
+
+    // Round Errors (added later)
+    #[msg("Round has not started yet")]
+    RoundNotStarted,
+
+    #[msg("Round status does not allow this transition")]
+    InvalidStatusTransition,
+
+    #[msg("Round has been cancelled")]
+    RoundCancelled,
+
+    #[msg("Round has not been cancelled")]
+    RoundNotCancelled,
+
+    #[msg("Round parameters can no longer be changed")]
+    RoundParamsLocked,
+
+    #[msg("Wallet is not on this round's allowlist")]
+    NotAllowlisted,
+
+    #[msg("Round is full")]
+    RoundFull,
+
+    #[msg("Round did not reach its minimum number of players")]
+    MinPlayersNotReached,
+
+    #[msg("Round is not underfilled")]
+    MinPlayersReached,
+
+    #[msg("Automatic round rollover is not enabled")]
+    RolloverDisabled,
+
+    #[msg("Previous round prizes have not been distributed yet")]
+    PayoutsNotProcessed,
+
+    #[msg("Rollover vault cannot cover the new round's costs")]
+    InsufficientRolloverFunds,
+
+    // Player Errors (added later)
+    #[msg("Reroll fee is below the minimum for this token's decimals")]
+    RerollFeeTooLow,
+
+    // Reward Errors (added later)
+    #[msg("The claim window for this round has closed")]
+    ClaimWindowExpired,
+
+    #[msg("The claim window for this round is still open")]
+    ClaimWindowOpen,
+
+    // Cleanup Errors
+    #[msg("Round prizes or refunds are not settled yet")]
+    RoundNotSettled,
+
+    #[msg("Player still has a prize or refund to claim")]
+    UnclaimedRewards,
+
+    #[msg("Round still has open player states")]
+    PlayerStatesOpen,
+
+    #[msg("The arena's current round is still needed to roll over")]
+    RolloverPending,
+
+    #[msg("No accounts to settle")]
+    NothingToSettle,
+
+    // Token Errors (added later)
+    #[msg("Accounts do not match the round's currency")]
+    InvalidCurrency,
+
+    // Migration Errors
+    #[msg("Account layout version is not supported")]
+    UnsupportedAccountVersion,
+
+    #[msg("Account is already at the current layout version")]
+    AlreadyMigrated,
+
+    // Treasury Errors
+    #[msg("Rake can only be withdrawn once the round's prizes are distributed")]
+    RakeLocked,
+
+    // Sponsor Errors
+    #[msg("Round still has open sponsor records")]
+    SponsorsOpen,
+
+    // Reward Errors (added later)
+    #[msg("The jackpot for this hour was already paid")]
+    JackpotAlreadyPaid,
+
+    #[msg("Remaining accounts must be each of the round's player states exactly once")]
+    PlayerStatesMismatch,
 }
@@ -1,10 +1,12 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, system_program};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{transfer, Mint, Token, TokenAccount, Transfer},
+    token_interface::{
+        close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
 };
 use ephemeral_rollups_sdk::anchor::delegate;
-use ephemeral_rollups_sdk::cpi::DelegateConfig;
 
 use crate::constants::*;
 use crate::errors::PandaBattleError;
@@ -27,65 +29,298 @@ pub struct InitializeGame<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn initialize_game(ctx: Context<InitializeGame>, token_mint: Pubkey) -> Result<()> {
+pub fn initialize_game(ctx: Context<InitializeGame>) -> Result<()> {
     let global_config = &mut ctx.accounts.global_config;
 
+    global_config.version = GLOBAL_CONFIG_VERSION;
     global_config.admin = ctx.accounts.admin.key();
-    global_config.token_mint = token_mint;
-    global_config.current_round = 0;
-    global_config.total_rounds = 0;
+    global_config.pending_admin = None;
+    global_config.operator = ctx.accounts.admin.key();
+    global_config.treasurer = ctx.accounts.admin.key();
+    global_config.total_arenas = 0;
+    global_config.paused_actions = 0;
+    global_config.protocol_fee_bps = 0;
+    global_config.delegation = DelegationSettings {
+        validator: Some(DEFAULT_ER_VALIDATOR),
+        commit_frequency_ms: DEFAULT_COMMIT_FREQUENCY_MS,
+    };
     global_config.bump = ctx.bumps.global_config;
 
+    msg!("Game initialized with admin: {}", global_config.admin);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(arena_id: u32)]
+pub struct CreateArena<'info> {
+    #[account(
+        mut,
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Token mint rounds in this arena are played with
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Arena::INIT_SPACE,
+        seeds = [
+            ARENA_SEED,
+            global_config.key().as_ref(),
+            arena_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub arena: Account<'info, Arena>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create an arena: an independent table with its own mint and round counter
+pub fn create_arena(ctx: Context<CreateArena>, arena_id: u32, name: [u8; 32]) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    let arena = &mut ctx.accounts.arena;
+
+    arena.global_config = global_config.key();
+    arena.arena_id = arena_id;
+    arena.name = name;
+    arena.token_mint = ctx.accounts.mint.key();
+    arena.current_round = 0;
+    arena.total_rounds = 0;
+    arena.protocol_fee_bps = global_config.protocol_fee_bps;
+    arena.delegation = global_config.delegation;
+    arena.rollover_template = None;
+    arena.rollover_requires_distribution = false;
+    arena.bump = ctx.bumps.arena;
+
+    global_config.total_arenas = global_config
+        .total_arenas
+        .checked_add(1)
+        .ok_or(PandaBattleError::Overflow)?;
+
     msg!(
-        "Game initialized with admin: {}, token_mint: {}",
-        global_config.admin,
-        token_mint
+        "Arena {} created with token_mint: {}",
+        arena_id,
+        arena.token_mint
     );
 
     Ok(())
 }
 
 #[derive(Accounts)]
-pub struct CreateRound<'info> {
+pub struct UpdateArena<'info> {
     #[account(
-        mut,
-        constraint = admin.key() == global_config.admin @ PandaBattleError::Unauthorized
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
     )]
     pub admin: Signer<'info>,
 
-    /// Token mint for this round
-    pub mint: Account<'info, Mint>,
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            ARENA_SEED,
+            global_config.key().as_ref(),
+            arena.arena_id.to_le_bytes().as_ref()
+        ],
+        bump = arena.bump
+    )]
+    pub arena: Account<'info, Arena>,
+
+    /// New token mint for future rounds (omit to keep the current one)
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+}
+
+/// Update an arena's own settings (only affects rounds created afterwards)
+pub fn update_arena(
+    ctx: Context<UpdateArena>,
+    protocol_fee_bps: Option<u16>,
+    delegation: Option<DelegationSettings>,
+) -> Result<()> {
+    let arena = &mut ctx.accounts.arena;
+
+    if let Some(mint) = &ctx.accounts.mint {
+        arena.token_mint = mint.key();
+    }
+
+    if let Some(fee_bps) = protocol_fee_bps {
+        require!(
+            fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            PandaBattleError::InvalidConfig
+        );
+        arena.protocol_fee_bps = fee_bps;
+    }
+
+    if let Some(delegation) = delegation {
+        delegation.validate()?;
+        arena.delegation = delegation;
+    }
+
+    msg!("Arena {} updated", arena.arena_id);
+
+    Ok(())
+}
 
+#[derive(Accounts)]
+#[instruction(rules_id: u32)]
+pub struct CreateRoundRules<'info> {
     #[account(
         mut,
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
     #[account(
         init,
         payer = admin,
+        space = 8 + RoundRules::INIT_SPACE,
+        seeds = [
+            ROUND_RULES_SEED,
+            global_config.key().as_ref(),
+            rules_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub round_rules: Account<'info, RoundRules>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Create an immutable set of round rules that rounds can reference
+pub fn create_round_rules(
+    ctx: Context<CreateRoundRules>,
+    rules_id: u32,
+    params: RoundRulesParams,
+) -> Result<()> {
+    let round_rules = &mut ctx.accounts.round_rules;
+
+    round_rules.apply(&params)?;
+    round_rules.global_config = ctx.accounts.global_config.key();
+    round_rules.rules_id = rules_id;
+    round_rules.bump = ctx.bumps.round_rules;
+
+    msg!(
+        "Round rules {} created. Max turns: {}, Starting: {}, Per hour: {}, Payout split: {}% to top {}",
+        rules_id,
+        round_rules.max_turns,
+        round_rules.starting_turns,
+        round_rules.turns_per_hour,
+        round_rules.top_pool_pct,
+        round_rules.leaderboard_size
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateRound<'info> {
+    #[account(
+        mut,
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub operator: Signer<'info>,
+
+    /// Token mint for this round (token rounds only)
+    #[account(address = arena.token_mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            ARENA_SEED,
+            global_config.key().as_ref(),
+            arena.arena_id.to_le_bytes().as_ref()
+        ],
+        bump = arena.bump
+    )]
+    pub arena: Account<'info, Arena>,
+
+    /// Balance rules for this round
+    #[account(
+        seeds = [
+            ROUND_RULES_SEED,
+            global_config.key().as_ref(),
+            round_rules.rules_id.to_le_bytes().as_ref()
+        ],
+        bump = round_rules.bump
+    )]
+    pub round_rules: Account<'info, RoundRules>,
+
+    #[account(
+        init,
+        payer = operator,
         space = 8 + GameRound::INIT_SPACE,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
-            (global_config.total_rounds + 1).to_le_bytes().as_ref()
+            arena.key().as_ref(),
+            (arena.total_rounds + 1).to_le_bytes().as_ref()
         ],
         bump
     )]
     pub game_round: Account<'info, GameRound>,
 
+    /// Token vault for this round (token rounds only)
     #[account(
         init,
-        payer = admin,
+        payer = operator,
         associated_token::mint = mint,
         associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + SolVault::INIT_SPACE,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    /// Leaderboard for this round, delegated with it so the reveal runs in the rollup
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub leaderboard: Account<'info, Leaderboard>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 
     /// CHECK: The buffer account
@@ -115,6 +350,33 @@ pub struct CreateRound<'info> {
     )]
     pub delegation_metadata_account: AccountInfo<'info>,
 
+    /// CHECK: The leaderboard's buffer account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::BUFFER, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = crate::id()
+    )]
+    pub leaderboard_buffer_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's delegation record account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_RECORD, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub leaderboard_delegation_record_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's delegation metadata account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_METADATA, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub leaderboard_delegation_metadata_account: AccountInfo<'info>,
+
     /// CHECK: The owner program of the pda
     #[account(address = crate::id())]
     pub owner_program: AccountInfo<'info>,
@@ -126,249 +388,1558 @@ pub struct CreateRound<'info> {
 
 pub fn create_round(
     ctx: Context<CreateRound>,
-    entry_fee: u64,
-    attack_pack_price: u64,
-    duration_secs: i64,
-    entry_hourly_inc_pct: u8,
+    params: RoundParams,
+    start_time: Option<i64>,
+    delegation: Option<DelegationSettings>,
+    currency: Currency,
 ) -> Result<()> {
-    {
-        let global_config = &mut ctx.accounts.global_config;
-        let game_round = &mut ctx.accounts.game_round;
-        let clock = Clock::get()?;
-
-        require!(duration_secs > 0, PandaBattleError::InvalidConfig);
-
-        global_config.current_round = global_config.total_rounds + 1;
-        global_config.total_rounds += 1;
-
-        game_round.global_config = global_config.key();
-        game_round.round_number = global_config.current_round;
-        game_round.entry_fee = entry_fee;
-        game_round.attack_pack_price = attack_pack_price;
-        game_round.duration_secs = duration_secs;
-        game_round.entry_hourly_inc_pct = entry_hourly_inc_pct;
-        game_round.start_time = clock.unix_timestamp;
-        game_round.end_time = clock.unix_timestamp + duration_secs;
-        game_round.leaderboard_reveal_ts = clock.unix_timestamp + (duration_secs / 2); // 12 hours for 24h round
-        game_round.prize_pool = 0;
-        game_round.player_count = 0;
-        game_round.total_battles = 0;
-        game_round.is_active = true;
-        game_round.payouts_processed = false;
-        game_round.bump = ctx.bumps.game_round;
-
-        msg!(
-        "Round {} created. Entry: {}, Pack: {}, Duration: {}s. Starts: {}, Ends: {}, Reveal: {}",
-        game_round.round_number,
-        entry_fee,
-        attack_pack_price,
-        duration_secs,
-        game_round.start_time,
-        game_round.end_time,
-        game_round.leaderboard_reveal_ts
-    );
-    }
+    let accounts = &mut *ctx.accounts;
+    accounts.game_round.bump = ctx.bumps.game_round;
 
-    {
-        msg!("Start delegate");
-
-        let admin = &ctx.accounts.admin;
-        let game_round = &ctx.accounts.game_round;
-        let owner_program = &ctx.accounts.owner_program;
-        let round_number_bytes = game_round.round_number.to_le_bytes();
-
-        let del_accounts = ephemeral_rollups_sdk::cpi::DelegateAccounts {
-            payer: &admin.to_account_info(),
-            pda: &game_round.to_account_info(),
-            owner_program: &owner_program.to_account_info(),
-            buffer: &ctx.accounts.buffer_account.to_account_info(),
-            delegation_record: &ctx.accounts.delegation_record_account.to_account_info(),
-            delegation_metadata: &ctx.accounts.delegation_metadata_account.to_account_info(),
-            delegation_program: &ctx.accounts.delegation_program.to_account_info(),
-            system_program: &ctx.accounts.system_program.to_account_info(),
-        };
-
-        let seeds = &[
-            GAME_ROUND_SEED,
-            game_round.global_config.as_ref(),
-            round_number_bytes.as_ref(),
-        ];
+    let mint = prepare_round_vault(
+        currency,
+        accounts.game_round.key(),
+        accounts.mint.as_ref(),
+        accounts.vault.as_ref(),
+        accounts.sol_vault.as_mut(),
+        ctx.bumps.sol_vault,
+    )?;
 
-        let config = DelegateConfig {
-            commit_frequency_ms: 30_000,
-            validator: Some(pubkey!("MAS1Dt9qreoRMQ14YQuhg8UTZMMzDdKhmkZMECCzk57")),
-        };
+    open_round(
+        &mut accounts.arena,
+        &mut accounts.game_round,
+        &accounts.round_rules,
+        mint,
+        Pubkey::default(),
+        &params,
+        start_time,
+    )?;
+    accounts.game_round.rent_payer = accounts.operator.key();
+    accounts.leaderboard.round = accounts.game_round.key();
+    accounts.leaderboard.bump = ctx.bumps.leaderboard;
 
-        game_round.exit(&crate::ID)?;
-        ephemeral_rollups_sdk::cpi::delegate_account(del_accounts, seeds, config)?;
+    // Per-round override of the global delegation settings
+    if let Some(delegation) = delegation {
+        delegation.validate()?;
+        accounts.game_round.delegation = delegation;
     }
 
-    Ok(())
+    delegate_new_round(
+        &accounts.game_round,
+        ephemeral_rollups_sdk::cpi::DelegateAccounts {
+            payer: &accounts.operator.to_account_info(),
+            pda: &accounts.game_round.to_account_info(),
+            owner_program: &accounts.owner_program.to_account_info(),
+            buffer: &accounts.buffer_account.to_account_info(),
+            delegation_record: &accounts.delegation_record_account.to_account_info(),
+            delegation_metadata: &accounts.delegation_metadata_account.to_account_info(),
+            delegation_program: &accounts.delegation_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+    )?;
+
+    delegate_new_leaderboard(
+        &accounts.game_round,
+        &accounts.leaderboard,
+        ephemeral_rollups_sdk::cpi::DelegateAccounts {
+            payer: &accounts.operator.to_account_info(),
+            pda: &accounts.leaderboard.to_account_info(),
+            owner_program: &accounts.owner_program.to_account_info(),
+            buffer: &accounts.leaderboard_buffer_account.to_account_info(),
+            delegation_record: &accounts
+                .leaderboard_delegation_record_account
+                .to_account_info(),
+            delegation_metadata: &accounts
+                .leaderboard_delegation_metadata_account
+                .to_account_info(),
+            delegation_program: &accounts.delegation_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+    )
 }
 
-#[delegate]
 #[derive(Accounts)]
-pub struct DelegateRound<'info> {
-    pub admin: Signer<'info>,
-    /// CHECK: Checked by the delegate program
-    pub validator: Option<AccountInfo<'info>>,
+#[instruction(template_id: u32)]
+pub struct CreateRoundTemplate<'info> {
     #[account(
         mut,
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Token mint the template's prices are denominated in (token templates only)
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Balance rules rounds from this template are played with
+    #[account(
         seeds = [
-            GAME_ROUND_SEED,
-            game_round.global_config.as_ref(),
-            game_round.round_number.to_le_bytes().as_ref()
+            ROUND_RULES_SEED,
+            global_config.key().as_ref(),
+            round_rules.rules_id.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump,
-        del
+        bump = round_rules.bump
     )]
-    pub game_round: Account<'info, GameRound>,
-}
+    pub round_rules: Account<'info, RoundRules>,
 
-pub fn delegate_round(ctx: Context<DelegateRound>) -> Result<()> {
-    let game_round = &ctx.accounts.game_round;
-    let round_number_bytes = game_round.round_number.to_le_bytes();
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RoundTemplate::INIT_SPACE,
+        seeds = [
+            ROUND_TEMPLATE_SEED,
+            global_config.key().as_ref(),
+            template_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub round_template: Account<'info, RoundTemplate>,
 
-    let seeds = &[
-        GAME_ROUND_SEED,
-        game_round.global_config.as_ref(),
-        round_number_bytes.as_ref(),
-    ];
+    pub system_program: Program<'info, System>,
+}
 
-    game_round.exit(&crate::ID)?;
+/// Create a named round template so daily rounds are reproducible from on-chain data
+pub fn create_round_template(
+    ctx: Context<CreateRoundTemplate>,
+    template_id: u32,
+    name: [u8; 32],
+    params: RoundParams,
+    currency: Currency,
+) -> Result<()> {
+    let (mint, decimals) = template_denomination(currency, ctx.accounts.mint.as_ref())?;
+    params.validate(decimals)?;
 
-    msg!("Delegating round {} to validator", game_round.round_number);
+    let round_template = &mut ctx.accounts.round_template;
+    round_template.global_config = ctx.accounts.global_config.key();
+    round_template.template_id = template_id;
+    round_template.name = name;
+    round_template.currency = currency;
+    round_template.mint = mint;
+    round_template.params = params;
+    round_template.rules = ctx.accounts.round_rules.key();
+    round_template.rounds_created = 0;
+    round_template.bump = ctx.bumps.round_template;
+
+    msg!(
+        "Round template {} created. Entry: {}, Pack: {}, Duration: {}s, Rules: {}",
+        template_id,
+        params.entry_fee,
+        params.attack_pack_price,
+        params.duration_secs,
+        ctx.accounts.round_rules.rules_id
+    );
 
-    ctx.accounts.delegate_game_round(
-        &ctx.accounts.admin,
-        seeds,
-        // &[
-        //     GAME_ROUND_SEED,
-        //     game_round.global_config.as_ref(),
-        //     game_round.round_number.to_le_bytes().as_ref(),
-        // ],
-        DelegateConfig::default(),
-    )?;
     Ok(())
 }
 
 #[derive(Accounts)]
-pub struct EndRound<'info> {
-    #[account(mut)]
+pub struct UpdateRoundTemplate<'info> {
+    #[account(
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
     pub admin: Signer<'info>,
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
+    /// Token mint the template's prices are denominated in (token templates only)
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [
+            ROUND_RULES_SEED,
+            global_config.key().as_ref(),
+            round_rules.rules_id.to_le_bytes().as_ref()
+        ],
+        bump = round_rules.bump
+    )]
+    pub round_rules: Account<'info, RoundRules>,
+
     #[account(
         mut,
         seeds = [
-            GAME_ROUND_SEED,
+            ROUND_TEMPLATE_SEED,
             global_config.key().as_ref(),
-            game_round.round_number.to_le_bytes().as_ref()
+            round_template.template_id.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump,
-        constraint = game_round.is_active @ PandaBattleError::RoundAlreadyEnded
+        bump = round_template.bump
     )]
-    pub game_round: Account<'info, GameRound>,
+    pub round_template: Account<'info, RoundTemplate>,
 }
 
-pub fn end_round(ctx: Context<EndRound>) -> Result<()> {
-    let game_round = &mut ctx.accounts.game_round;
-    let clock = Clock::get()?;
-
-    require!(game_round.is_active, PandaBattleError::RoundAlreadyEnded);
-
-    // Allow early end by admin or auto-end after duration
-    let is_admin = ctx.accounts.admin.key() == ctx.accounts.global_config.admin;
-    let is_expired = clock.unix_timestamp >= game_round.end_time;
-
-    require!(is_admin || is_expired, PandaBattleError::Unauthorized);
+/// Update a template's parameters and rules (only affects rounds created afterwards)
+pub fn update_round_template(
+    ctx: Context<UpdateRoundTemplate>,
+    name: Option<[u8; 32]>,
+    params: RoundParams,
+    currency: Currency,
+) -> Result<()> {
+    let (mint, decimals) = template_denomination(currency, ctx.accounts.mint.as_ref())?;
+    params.validate(decimals)?;
 
-    game_round.is_active = false;
-    game_round.end_time = clock.unix_timestamp;
+    let round_template = &mut ctx.accounts.round_template;
+    if let Some(name) = name {
+        round_template.name = name;
+    }
+    round_template.currency = currency;
+    round_template.mint = mint;
+    round_template.params = params;
+    round_template.rules = ctx.accounts.round_rules.key();
 
     msg!(
-        "Round {} ended. Total prize pool: {}, Players: {}, Battles: {}",
-        game_round.round_number,
-        game_round.prize_pool,
-        game_round.player_count,
-        game_round.total_battles
+        "Round template {} updated. Entry: {}, Pack: {}, Duration: {}s, Rules: {}",
+        round_template.template_id,
+        params.entry_fee,
+        params.attack_pack_price,
+        params.duration_secs,
+        ctx.accounts.round_rules.rules_id
     );
 
     Ok(())
 }
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
+pub struct CreateRoundFromTemplate<'info> {
     #[account(
-        constraint = admin.key() == global_config.admin @ PandaBattleError::Unauthorized
+        mut,
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
     )]
-    pub admin: Signer<'info>,
+    pub operator: Signer<'info>,
+
+    /// Token mint for this round (token templates only)
+    #[account(address = arena.token_mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
 
     #[account(
-        mut,
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
-}
 
-pub fn update_config(ctx: Context<UpdateConfig>, token_mint: Option<Pubkey>) -> Result<()> {
-    let global_config = &mut ctx.accounts.global_config;
+    #[account(
+        mut,
+        seeds = [
+            ARENA_SEED,
+            global_config.key().as_ref(),
+            arena.arena_id.to_le_bytes().as_ref()
+        ],
+        bump = arena.bump
+    )]
+    pub arena: Account<'info, Arena>,
 
-    if let Some(mint) = token_mint {
-        global_config.token_mint = mint;
-    }
+    #[account(
+        mut,
+        seeds = [
+            ROUND_TEMPLATE_SEED,
+            global_config.key().as_ref(),
+            round_template.template_id.to_le_bytes().as_ref()
+        ],
+        bump = round_template.bump,
+        constraint = round_template.fits_arena(&arena) @ PandaBattleError::InvalidMint
+    )]
+    pub round_template: Account<'info, RoundTemplate>,
 
-    msg!("Global config updated");
+    #[account(address = round_template.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
+
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + GameRound::INIT_SPACE,
+        seeds = [
+            GAME_ROUND_SEED,
+            arena.key().as_ref(),
+            (arena.total_rounds + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// Token vault for this round (token templates only)
+    #[account(
+        init,
+        payer = operator,
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL templates only)
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + SolVault::INIT_SPACE,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    /// Leaderboard for this round, delegated with it so the reveal runs in the rollup
+    #[account(
+        init,
+        payer = operator,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: The buffer account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::BUFFER, game_round.key().as_ref()],
+        bump,
+        seeds::program = crate::id()
+    )]
+    pub buffer_account: AccountInfo<'info>,
+
+    /// CHECK: The delegation record account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_RECORD, game_round.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub delegation_record_account: AccountInfo<'info>,
+
+    /// CHECK: The delegation metadata account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_METADATA, game_round.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub delegation_metadata_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's buffer account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::BUFFER, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = crate::id()
+    )]
+    pub leaderboard_buffer_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's delegation record account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_RECORD, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub leaderboard_delegation_record_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's delegation metadata account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_METADATA, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub leaderboard_delegation_metadata_account: AccountInfo<'info>,
+
+    /// CHECK: The owner program of the pda
+    #[account(address = crate::id())]
+    pub owner_program: AccountInfo<'info>,
+
+    /// CHECK: The delegation program
+    #[account(address = ::ephemeral_rollups_sdk::id())]
+    pub delegation_program: AccountInfo<'info>,
+}
+
+pub fn create_round_from_template(
+    ctx: Context<CreateRoundFromTemplate>,
+    start_time: Option<i64>,
+) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    accounts.game_round.bump = ctx.bumps.game_round;
+
+    let mint = prepare_round_vault(
+        accounts.round_template.currency,
+        accounts.game_round.key(),
+        accounts.mint.as_ref(),
+        accounts.vault.as_ref(),
+        accounts.sol_vault.as_mut(),
+        ctx.bumps.sol_vault,
+    )?;
+
+    let params = accounts.round_template.params;
+    open_round(
+        &mut accounts.arena,
+        &mut accounts.game_round,
+        &accounts.round_rules,
+        mint,
+        accounts.round_template.key(),
+        &params,
+        start_time,
+    )?;
+    accounts.game_round.rent_payer = accounts.operator.key();
+    accounts.leaderboard.round = accounts.game_round.key();
+    accounts.leaderboard.bump = ctx.bumps.leaderboard;
+
+    accounts.round_template.rounds_created = accounts
+        .round_template
+        .rounds_created
+        .checked_add(1)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    msg!(
+        "Round {} created from template {}",
+        accounts.game_round.round_number,
+        accounts.round_template.template_id
+    );
+
+    delegate_new_round(
+        &accounts.game_round,
+        ephemeral_rollups_sdk::cpi::DelegateAccounts {
+            payer: &accounts.operator.to_account_info(),
+            pda: &accounts.game_round.to_account_info(),
+            owner_program: &accounts.owner_program.to_account_info(),
+            buffer: &accounts.buffer_account.to_account_info(),
+            delegation_record: &accounts.delegation_record_account.to_account_info(),
+            delegation_metadata: &accounts.delegation_metadata_account.to_account_info(),
+            delegation_program: &accounts.delegation_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+    )?;
+
+    delegate_new_leaderboard(
+        &accounts.game_round,
+        &accounts.leaderboard,
+        ephemeral_rollups_sdk::cpi::DelegateAccounts {
+            payer: &accounts.operator.to_account_info(),
+            pda: &accounts.leaderboard.to_account_info(),
+            owner_program: &accounts.owner_program.to_account_info(),
+            buffer: &accounts.leaderboard_buffer_account.to_account_info(),
+            delegation_record: &accounts
+                .leaderboard_delegation_record_account
+                .to_account_info(),
+            delegation_metadata: &accounts
+                .leaderboard_delegation_metadata_account
+                .to_account_info(),
+            delegation_program: &accounts.delegation_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+    )
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateRound<'info> {
+    #[account(
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub operator: Signer<'info>,
+    /// CHECK: Checked by the delegate program
+    pub validator: Option<AccountInfo<'info>>,
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        del,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub game_round: Account<'info, GameRound>,
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump = leaderboard.bump,
+        del
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+}
+
+pub fn delegate_round(ctx: Context<DelegateRound>) -> Result<()> {
+    let game_round = &ctx.accounts.game_round;
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+
+    let seeds = &[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+    ];
+
+    game_round.exit(&crate::ID)?;
+
+    msg!("Delegating round {} to validator", game_round.round_number);
+
+    ctx.accounts.delegate_game_round(
+        &ctx.accounts.operator,
+        seeds,
+        // &[
+        //     GAME_ROUND_SEED,
+        //     game_round.arena.as_ref(),
+        //     game_round.round_number.to_le_bytes().as_ref(),
+        // ],
+        game_round.delegation.delegate_config(),
+    )?;
+
+    // The leaderboard goes with the round so the reveal can run in the rollup
+    let round_key = game_round.key();
+    ctx.accounts.leaderboard.exit(&crate::ID)?;
+    ctx.accounts.delegate_leaderboard(
+        &ctx.accounts.operator,
+        &[LEADERBOARD_SEED, round_key.as_ref()],
+        game_round.delegation.delegate_config(),
+    )?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct EndRound<'info> {
+    /// Operator can end early; anyone can end after expiry
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.is_open() @ PandaBattleError::RoundAlreadyEnded
+    )]
+    pub game_round: Account<'info, GameRound>,
+}
+
+pub fn end_round(ctx: Context<EndRound>) -> Result<()> {
+    let game_round = &mut ctx.accounts.game_round;
+    let clock = Clock::get()?;
+
+    // Allow early end by operator or auto-end after duration
+    let is_operator = ctx
+        .accounts
+        .global_config
+        .is_operator(&ctx.accounts.caller.key());
+    let is_expired = clock.unix_timestamp >= game_round.end_time;
+
+    require!(is_operator || is_expired, PandaBattleError::Unauthorized);
+
+    // Rounds that never started are cancelled instead
+    game_round.sync_status(clock.unix_timestamp);
+    game_round.transition_to(RoundStatus::Ended)?;
+    game_round.end_time = clock.unix_timestamp;
+
+    msg!(
+        "Round {} ended. Total prize pool: {}, Players: {}, Battles: {}",
+        game_round.round_number,
+        game_round.prize_pool,
+        game_round.player_count,
+        game_round.total_battles
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateRoundParams<'info> {
+    #[account(
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub operator: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        // Once the leaderboard is revealed the round's economics are final
+        constraint = matches!(game_round.status, RoundStatus::Scheduled | RoundStatus::Active)
+            @ PandaBattleError::RoundParamsLocked
+    )]
+    pub game_round: Account<'info, GameRound>,
+}
+
+/// Fix a round's parameters (and optionally reschedule it) before anyone has joined
+/// or while it is still in registration
+pub fn update_round_params(
+    ctx: Context<UpdateRoundParams>,
+    params: RoundParams,
+    start_time: Option<i64>,
+) -> Result<()> {
+    let game_round = &mut ctx.accounts.game_round;
+    let clock = Clock::get()?;
+
+    require!(
+        game_round.params_editable(clock.unix_timestamp),
+        PandaBattleError::RoundParamsLocked
+    );
+    // Registrations already taken must still fit under a new cap
+    require!(
+        params.max_players == 0 || params.max_players >= game_round.player_count,
+        PandaBattleError::InvalidConfig
+    );
+
+    let decimals = match game_round.currency {
+        Currency::Token => {
+            ctx.accounts
+                .mint
+                .as_ref()
+                .ok_or(PandaBattleError::InvalidCurrency)?
+                .decimals
+        }
+        Currency::NativeSol => NATIVE_SOL_DECIMALS,
+    };
+    params.validate(decimals)?;
+
+    if let Some(start_time) = start_time {
+        require!(
+            start_time >= clock.unix_timestamp,
+            PandaBattleError::InvalidConfig
+        );
+    }
+    let start_time = start_time.unwrap_or(game_round.start_time);
+
+    game_round.apply_params(&params, start_time)?;
+
+    // Only an empty round can be edited after its start, so moving the start of one
+    // that already went active into the future puts it back into registration
+    if game_round.status == RoundStatus::Active && !game_round.has_started(clock.unix_timestamp) {
+        game_round.transition_to(RoundStatus::Scheduled)?;
+    }
+    game_round.sync_status(clock.unix_timestamp);
+
+    msg!(
+        "Round {} updated. Entry: {}, Pack: {}, Rerolls: {:?}, Duration: {}s. Starts: {}, Ends: {}, Reveal: {}",
+        game_round.round_number,
+        params.entry_fee,
+        params.attack_pack_price,
+        params.reroll_fees,
+        params.duration_secs,
+        game_round.start_time,
+        game_round.end_time,
+        game_round.leaderboard_reveal_ts
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRoundAllowlist<'info> {
+    #[account(
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub operator: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.is_open() @ PandaBattleError::RoundNotActive
+    )]
+    pub game_round: Account<'info, GameRound>,
+}
+
+/// Restrict joins to a Merkle allowlist of wallets (None opens the round to everyone).
+/// Players who already joined are unaffected.
+pub fn set_round_allowlist(
+    ctx: Context<SetRoundAllowlist>,
+    allowlist_root: Option<[u8; 32]>,
+) -> Result<()> {
+    let game_round = &mut ctx.accounts.game_round;
+
+    game_round.allowlist_root = allowlist_root;
+
+    msg!(
+        "Round {} allowlist {}",
+        game_round.round_number,
+        if allowlist_root.is_some() {
+            "set"
+        } else {
+            "cleared"
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelRound<'info> {
+    #[account(
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub operator: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status.can_transition_to(RoundStatus::Cancelled)
+            @ PandaBattleError::InvalidStatusTransition
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// CHECK: PDA checked by seeds; folded into the pool in the handler if it exists
+    #[account(
+        seeds = [PENDING_DEPOSITS_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub pending_deposits: UncheckedAccount<'info>,
+}
+
+/// Abort a round: players reclaim what they paid via `claim_refund`
+pub fn cancel_round(ctx: Context<CancelRound>) -> Result<()> {
+    let game_round = &mut ctx.accounts.game_round;
+
+    fold_pending_deposits(game_round, &ctx.accounts.pending_deposits)?;
+    game_round.cancel(Clock::get()?.unix_timestamp)?;
+
+    msg!(
+        "Round {} cancelled. Refundable pool: {}, Players: {}",
+        game_round.round_number,
+        game_round.prize_pool,
+        game_round.player_count
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Update global defaults (snapshotted into arenas created afterwards)
+pub fn update_config(ctx: Context<UpdateConfig>, protocol_fee_bps: Option<u16>) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+
+    if let Some(fee_bps) = protocol_fee_bps {
+        require!(
+            fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            PandaBattleError::InvalidConfig
+        );
+        global_config.protocol_fee_bps = fee_bps;
+    }
+
+    msg!("Global config updated");
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Propose a new admin (step 1 of handover). Passing `None` cancels a pending proposal.
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Option<Pubkey>) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+
+    global_config.pending_admin = new_admin;
+
+    match new_admin {
+        Some(new_admin) => msg!("Admin handover proposed to {}", new_admin),
+        None => msg!("Pending admin handover cancelled"),
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Accept a pending admin proposal (step 2 of handover, signed by the new admin)
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+    let new_admin = ctx.accounts.new_admin.key();
+
+    let pending_admin = global_config
+        .pending_admin
+        .ok_or(PandaBattleError::NoPendingAdmin)?;
+    require_keys_eq!(pending_admin, new_admin, PandaBattleError::Unauthorized);
+
+    let previous_admin = global_config.admin;
+    global_config.admin = new_admin;
+    global_config.pending_admin = None;
+
+    msg!("Admin handed over from {} to {}", previous_admin, new_admin);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    #[account(
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Reassign the operator and/or treasurer roles (admin only)
+pub fn set_roles(
+    ctx: Context<SetRoles>,
+    operator: Option<Pubkey>,
+    treasurer: Option<Pubkey>,
+) -> Result<()> {
+    let global_config = &mut ctx.accounts.global_config;
+
+    if let Some(operator) = operator {
+        global_config.operator = operator;
+    }
+
+    if let Some(treasurer) = treasurer {
+        global_config.treasurer = treasurer;
+    }
+
+    msg!(
+        "Roles updated. Operator: {}, Treasurer: {}",
+        global_config.operator,
+        global_config.treasurer
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetPause<'info> {
+    #[account(
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Emergency circuit breaker: replace the paused actions bitmask (0 resumes everything)
+pub fn set_pause(ctx: Context<SetPause>, paused_actions: u8) -> Result<()> {
+    require!(
+        paused_actions & !PAUSE_ALL == 0,
+        PandaBattleError::InvalidConfig
+    );
+
+    let global_config = &mut ctx.accounts.global_config;
+    global_config.paused_actions = paused_actions;
+
+    msg!("Paused actions set to {:#08b}", paused_actions);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetDelegationSettings<'info> {
+    #[account(
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+}
+
+/// Set the default ER validator and commit frequency for rounds created afterwards
+pub fn set_delegation_settings(
+    ctx: Context<SetDelegationSettings>,
+    delegation: DelegationSettings,
+) -> Result<()> {
+    delegation.validate()?;
+    ctx.accounts.global_config.delegation = delegation;
+
+    msg!(
+        "Delegation settings updated. Validator: {:?}, commit frequency: {}ms",
+        delegation.validator,
+        delegation.commit_frequency_ms
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRollover<'info> {
+    #[account(
+        mut,
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            ARENA_SEED,
+            global_config.key().as_ref(),
+            arena.arena_id.to_le_bytes().as_ref()
+        ],
+        bump = arena.bump
+    )]
+    pub arena: Account<'info, Arena>,
+
+    /// Template to roll over into; omit to disable rollover
+    #[account(
+        seeds = [
+            ROUND_TEMPLATE_SEED,
+            global_config.key().as_ref(),
+            round_template.template_id.to_le_bytes().as_ref()
+        ],
+        bump = round_template.bump,
+        constraint = round_template.fits_arena(&arena) @ PandaBattleError::InvalidMint
+    )]
+    pub round_template: Option<Account<'info, RoundTemplate>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RolloverVault::INIT_SPACE,
+        seeds = [ROLLOVER_VAULT_SEED, global_config.key().as_ref()],
+        bump
+    )]
+    pub rollover_vault: Account<'info, RolloverVault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Configure the permissionless `start_next_round` crank for an arena
+pub fn set_rollover(ctx: Context<SetRollover>, requires_distribution: bool) -> Result<()> {
+    let arena = &mut ctx.accounts.arena;
+    let rollover_vault = &mut ctx.accounts.rollover_vault;
+
+    arena.rollover_template = ctx.accounts.round_template.as_ref().map(|t| t.key());
+    arena.rollover_requires_distribution = requires_distribution;
+
+    rollover_vault.global_config = ctx.accounts.global_config.key();
+    rollover_vault.bump = ctx.bumps.rollover_vault;
+
+    msg!(
+        "Arena {} rollover template: {:?}, requires distribution: {}",
+        arena.arena_id,
+        arena.rollover_template,
+        requires_distribution
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        mut,
+        constraint = global_config.is_treasurer(&treasurer.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub treasurer: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        // Cancelled rounds refund the rake, and players' refunds rely on it until then
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status == RoundStatus::Distributed @ PandaBattleError::RakeLocked
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
+    #[account(
+        mut,
+        constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    /// Destination for the withdrawn rake (token rounds only; SOL rake goes to the treasurer)
+    #[account(
+        mut,
+        token::authority = treasurer,
+        constraint = treasury_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Withdraw accrued protocol rake from a distributed round's vault (never touches the prize pool)
+pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+    let game_round = &mut ctx.accounts.game_round;
+
+    require!(amount > 0, PandaBattleError::InvalidConfig);
+    require!(
+        amount <= game_round.protocol_fees,
+        PandaBattleError::InsufficientFunds
+    );
+
+    game_round.protocol_fees -= amount;
+
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+        &[game_round.bump],
+    ]];
+
+    RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .pay_out(
+        &ctx.accounts.treasurer.to_account_info(),
+        ctx.accounts.treasury_token_account.as_ref(),
+        &game_round.to_account_info(),
+        signer_seeds,
+        amount,
+    )?;
+
+    msg!(
+        "Treasury withdrew {} tokens of protocol fees from round {}. Remaining: {}",
+        amount,
+        game_round.round_number,
+        game_round.protocol_fees
+    );
 
     Ok(())
 }
 
 // ============== UTILITY FUNCTIONS ==============
 
+/// Initialize a freshly created round from its parameters and advance the arena's round counter
+pub fn open_round(
+    arena: &mut Account<Arena>,
+    game_round: &mut Account<GameRound>,
+    round_rules: &Account<RoundRules>,
+    mint: Option<&InterfaceAccount<Mint>>,
+    template: Pubkey,
+    params: &RoundParams,
+    start_time: Option<i64>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+
+    // Rounds can be scheduled ahead of time; joins before the start are registrations
+    let start_time = start_time.unwrap_or(clock.unix_timestamp);
+
+    // No mint means a native SOL round
+    let (currency, mint, decimals) = match mint {
+        Some(mint) => (Currency::Token, mint.key(), mint.decimals),
+        None => (Currency::NativeSol, Pubkey::default(), NATIVE_SOL_DECIMALS),
+    };
+
+    params.validate(decimals)?;
+    require!(
+        start_time >= clock.unix_timestamp,
+        PandaBattleError::InvalidConfig
+    );
+
+    arena.current_round = arena.total_rounds + 1;
+    arena.total_rounds += 1;
+
+    game_round.version = GAME_ROUND_VERSION;
+    game_round.global_config = arena.global_config;
+    game_round.arena = arena.key();
+    game_round.currency = currency;
+    game_round.mint = mint;
+    game_round.round_number = arena.current_round;
+    game_round.apply_params(params, start_time)?;
+    game_round.rules = round_rules.key();
+    game_round.template = template;
+    game_round.delegation = arena.delegation;
+    game_round.allowlist_root = None;
+    game_round.prize_pool = 0;
+    game_round.sponsored_amount = 0;
+    game_round.players_paid = 0;
+    game_round.refund_pool = 0;
+    game_round.protocol_fee_bps = arena.protocol_fee_bps;
+    game_round.protocol_fees = 0;
+    game_round.player_count = 0;
+    game_round.open_player_states = 0;
+    game_round.open_sponsors = 0;
+    game_round.total_battles = 0;
+    game_round.last_jackpot_hour = None;
+    game_round.status = RoundStatus::Scheduled;
+    game_round.sync_status(clock.unix_timestamp);
+    game_round.claim_deadline = 0;
+    game_round.unclaimed_swept = false;
+
+    msg!(
+        "Arena {} round {} created ({:?}). Entry: {}, Pack: {}, Rerolls: {:?}, Duration: {}s. Starts: {}, Ends: {}, Reveal: {}, Rules: {}",
+        arena.arena_id,
+        game_round.round_number,
+        currency,
+        params.entry_fee,
+        params.attack_pack_price,
+        params.reroll_fees,
+        params.duration_secs,
+        game_round.start_time,
+        game_round.end_time,
+        game_round.leaderboard_reveal_ts,
+        round_rules.rules_id
+    );
+
+    Ok(())
+}
+
+/// Check a new round was given the vault its currency needs and record the lamport vault
+/// of a SOL round; returns the mint to open the round with (None for native SOL)
+pub fn prepare_round_vault<'a, 'info>(
+    currency: Currency,
+    game_round: Pubkey,
+    mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    vault: Option<&InterfaceAccount<'info, TokenAccount>>,
+    sol_vault: Option<&mut Account<'info, SolVault>>,
+    sol_vault_bump: Option<u8>,
+) -> Result<Option<&'a InterfaceAccount<'info, Mint>>> {
+    match currency {
+        // Token rounds need the arena mint and its vault
+        Currency::Token => {
+            require!(
+                mint.is_some() && vault.is_some(),
+                PandaBattleError::InvalidCurrency
+            );
+            Ok(mint)
+        }
+        // SOL rounds need the lamport vault
+        Currency::NativeSol => {
+            let sol_vault = sol_vault.ok_or(PandaBattleError::InvalidCurrency)?;
+            sol_vault.game_round = game_round;
+            sol_vault.bump = sol_vault_bump.ok_or(PandaBattleError::InvalidCurrency)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Key and decimals a template's prices are denominated in (native SOL has no mint)
+fn template_denomination(
+    currency: Currency,
+    mint: Option<&InterfaceAccount<Mint>>,
+) -> Result<(Pubkey, u8)> {
+    match currency {
+        Currency::Token => {
+            let mint = mint.ok_or(PandaBattleError::InvalidCurrency)?;
+            Ok((mint.key(), mint.decimals))
+        }
+        Currency::NativeSol => Ok((Pubkey::default(), NATIVE_SOL_DECIMALS)),
+    }
+}
+
+/// Delegate a newly created round to the ephemeral rollup
+pub fn delegate_new_round<'info>(
+    game_round: &Account<'info, GameRound>,
+    del_accounts: ephemeral_rollups_sdk::cpi::DelegateAccounts<'_, 'info>,
+) -> Result<()> {
+    msg!("Start delegate");
+
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+    let seeds = &[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+    ];
+
+    game_round.exit(&crate::ID)?;
+    ephemeral_rollups_sdk::cpi::delegate_account(
+        del_accounts,
+        seeds,
+        game_round.delegation.delegate_config(),
+    )?;
+
+    Ok(())
+}
+
+/// Delegate a newly created round's leaderboard with the round's settings, so the reveal
+/// can run in the rollup
+pub fn delegate_new_leaderboard<'info>(
+    game_round: &Account<'info, GameRound>,
+    leaderboard: &Account<'info, Leaderboard>,
+    del_accounts: ephemeral_rollups_sdk::cpi::DelegateAccounts<'_, 'info>,
+) -> Result<()> {
+    let round_key = game_round.key();
+    let seeds = &[LEADERBOARD_SEED, round_key.as_ref()];
+
+    leaderboard.exit(&crate::ID)?;
+    ephemeral_rollups_sdk::cpi::delegate_account(
+        del_accounts,
+        seeds,
+        game_round.delegation.delegate_config(),
+    )?;
+
+    Ok(())
+}
+
+/// Fold a round's `PendingDeposits` into its pool, if any deposits were made
+pub fn fold_pending_deposits(game_round: &mut GameRound, pending: &AccountInfo) -> Result<()> {
+    // The account only exists once something was deposited
+    if *pending.owner != crate::ID {
+        return Ok(());
+    }
+    let pending = PendingDeposits::try_deserialize(&mut &pending.try_borrow_data()?[..])?;
+    game_round.fold_pending_deposits(&pending)
+}
+
+/// Move a payment into a round vault and return the amount the vault actually
+/// received (less than `amount` when the mint charges a transfer fee)
 pub fn transfer_to_vault<'info>(
-    from: &Account<'info, TokenAccount>,
-    to: &Account<'info, TokenAccount>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
     authority: &Signer<'info>,
-    token_program: &Program<'info, Token>,
+    token_program: &Interface<'info, TokenInterface>,
     amount: u64,
-) -> Result<()> {
-    let cpi_accounts = Transfer {
+) -> Result<u64> {
+    let balance_before = to.amount;
+
+    let cpi_accounts = TransferChecked {
         from: from.to_account_info(),
+        mint: mint.to_account_info(),
         to: to.to_account_info(),
         authority: authority.to_account_info(),
     };
 
     let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
-    transfer(cpi_ctx, amount)?;
+    transfer_checked(cpi_ctx, amount, mint.decimals)?;
 
-    msg!("Transferred {} tokens to vault", amount);
-    Ok(())
+    to.reload()?;
+    let received = to
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    msg!(
+        "Transferred {} tokens to vault ({} received)",
+        amount,
+        received
+    );
+    Ok(received)
 }
 
 pub fn transfer_from_vault<'info>(
-    from: &Account<'info, TokenAccount>,
-    to: &Account<'info, TokenAccount>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
     authority: &AccountInfo<'info>,
-    token_program: &Program<'info, Token>,
+    token_program: &Interface<'info, TokenInterface>,
     amount: u64,
     signer_seeds: &[&[&[u8]]],
 ) -> Result<()> {
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from: from.to_account_info(),
+        mint: mint.to_account_info(),
         to: to.to_account_info(),
         authority: authority.to_account_info(),
     };
 
     let cpi_ctx =
         CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
-    transfer(cpi_ctx, amount)?;
+    transfer_checked(cpi_ctx, amount, mint.decimals)?;
 
     msg!("Transferred {} tokens from vault", amount);
     Ok(())
 }
+
+/// A round's vault accounts, moving funds in whichever currency the round uses.
+/// Token rounds need the mint and vault ATA; native SOL rounds need the `SolVault`.
+pub struct RoundVault<'a, 'info> {
+    pub currency: Currency,
+    pub mint: Option<&'a InterfaceAccount<'info, Mint>>,
+    pub vault: Option<&'a mut InterfaceAccount<'info, TokenAccount>>,
+    pub sol_vault: Option<&'a Account<'info, SolVault>>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub system_program: &'a Program<'info, System>,
+}
+
+impl<'info> RoundVault<'_, 'info> {
+    /// Funds held by the vault, excluding the SOL vault's rent-exempt reserve
+    pub fn balance(&self) -> Result<u64> {
+        match self.currency {
+            Currency::Token => Ok(self
+                .vault
+                .as_ref()
+                .ok_or(PandaBattleError::InvalidCurrency)?
+                .amount),
+            Currency::NativeSol => {
+                let vault_info = self
+                    .sol_vault
+                    .ok_or(PandaBattleError::InvalidCurrency)?
+                    .to_account_info();
+                let reserve = Rent::get()?.minimum_balance(vault_info.data_len());
+                Ok(vault_info.lamports().saturating_sub(reserve))
+            }
+        }
+    }
+
+    /// Collect a payment from `payer` and return the amount the vault actually received
+    pub fn deposit(
+        self,
+        payer: &Signer<'info>,
+        payer_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        amount: u64,
+    ) -> Result<u64> {
+        match self.currency {
+            Currency::Token => transfer_to_vault(
+                payer_token_account.ok_or(PandaBattleError::InvalidCurrency)?,
+                self.vault.ok_or(PandaBattleError::InvalidCurrency)?,
+                self.mint.ok_or(PandaBattleError::InvalidCurrency)?,
+                payer,
+                self.token_program,
+                amount,
+            ),
+            Currency::NativeSol => {
+                let sol_vault = self.sol_vault.ok_or(PandaBattleError::InvalidCurrency)?;
+
+                let cpi_accounts = system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: sol_vault.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+                system_program::transfer(cpi_ctx, amount)?;
+
+                msg!("Transferred {} lamports to vault", amount);
+                Ok(amount)
+            }
+        }
+    }
+
+    /// Pay `amount` out of the vault: to `recipient_token_account` for token rounds
+    /// (signed by the round PDA), or straight to the `recipient` wallet for SOL rounds
+    pub fn pay_out(
+        self,
+        recipient: &AccountInfo<'info>,
+        recipient_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        authority: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+        amount: u64,
+    ) -> Result<()> {
+        match self.currency {
+            Currency::Token => transfer_from_vault(
+                self.vault.ok_or(PandaBattleError::InvalidCurrency)?,
+                recipient_token_account.ok_or(PandaBattleError::InvalidCurrency)?,
+                self.mint.ok_or(PandaBattleError::InvalidCurrency)?,
+                authority,
+                self.token_program,
+                amount,
+                signer_seeds,
+            ),
+            Currency::NativeSol => {
+                let vault_info = self
+                    .sol_vault
+                    .ok_or(PandaBattleError::InvalidCurrency)?
+                    .to_account_info();
+
+                // The vault keeps its rent-exempt reserve
+                let reserve = Rent::get()?.minimum_balance(vault_info.data_len());
+                require!(
+                    vault_info.lamports().saturating_sub(reserve) >= amount,
+                    PandaBattleError::InsufficientFunds
+                );
+
+                **vault_info.try_borrow_mut_lamports()? -= amount;
+                **recipient.try_borrow_mut_lamports()? += amount;
+
+                msg!("Transferred {} lamports from vault", amount);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sweep whatever is left in the vault to the treasury (`treasury_token_account`
+    /// for token rounds, the `treasurer` wallet for SOL rounds), then close the vault
+    /// and return its rent to `rent_receiver`. Returns the amount swept.
+    pub fn close(
+        self,
+        treasurer: &AccountInfo<'info>,
+        treasury_token_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+        rent_receiver: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<u64> {
+        match self.currency {
+            Currency::Token => {
+                let vault = self.vault.ok_or(PandaBattleError::InvalidCurrency)?;
+                let residual = vault.amount;
+                if residual > 0 {
+                    transfer_from_vault(
+                        vault,
+                        treasury_token_account.ok_or(PandaBattleError::InvalidCurrency)?,
+                        self.mint.ok_or(PandaBattleError::InvalidCurrency)?,
+                        authority,
+                        self.token_program,
+                        residual,
+                        signer_seeds,
+                    )?;
+                }
+
+                let cpi_accounts = CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: rent_receiver.clone(),
+                    authority: authority.clone(),
+                };
+                let cpi_ctx = CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer_seeds,
+                );
+                close_account(cpi_ctx)?;
+
+                Ok(residual)
+            }
+            Currency::NativeSol => {
+                let sol_vault = self.sol_vault.ok_or(PandaBattleError::InvalidCurrency)?;
+                let vault_info = sol_vault.to_account_info();
+
+                let reserve = Rent::get()?.minimum_balance(vault_info.data_len());
+                let residual = vault_info.lamports().saturating_sub(reserve);
+                **vault_info.try_borrow_mut_lamports()? -= residual;
+                **treasurer.try_borrow_mut_lamports()? += residual;
+
+                sol_vault.close(rent_receiver.clone())?;
+
+                Ok(residual)
+            }
+        }
+    }
+}
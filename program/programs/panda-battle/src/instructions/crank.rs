@@ -1,17 +1,30 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use ephemeral_rollups_sdk::anchor::commit;
 
 use crate::constants::*;
 use crate::errors::PandaBattleError;
+use crate::instructions::admin::{
+    delegate_new_leaderboard, delegate_new_round, fold_pending_deposits, open_round,
+    prepare_round_vault, RoundVault,
+};
 use crate::state::*;
 
 /// Regenerate turns for a player (can be called by anyone - crank)
 pub fn regenerate_turns(ctx: Context<RegenerateTurns>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
     let player_state = &mut ctx.accounts.player_state;
     let game_round = &ctx.accounts.game_round;
+    let rules = &ctx.accounts.round_rules;
     let clock = Clock::get()?;
 
-    require!(game_round.is_active, PandaBattleError::RoundNotActive);
+    require!(game_round.is_open(), PandaBattleError::RoundNotActive);
 
     // Check if enough time has passed for regeneration
     let time_since_last_regen = clock.unix_timestamp - player_state.last_turn_regen;
@@ -22,7 +35,7 @@ pub fn regenerate_turns(ctx: Context<RegenerateTurns>) -> Result<()> {
 
     // Calculate how many regeneration cycles have passed
     let cycles = (time_since_last_regen / TURN_REGEN_INTERVAL) as u8;
-    let turns_to_add = cycles * TURNS_PER_HOUR;
+    let turns_to_add = cycles.saturating_mul(rules.turns_per_hour);
 
     // Check if player is already at max turns
     require!(
@@ -36,8 +49,7 @@ pub fn regenerate_turns(ctx: Context<RegenerateTurns>) -> Result<()> {
 
     // Update last regeneration timestamp
     // Align to the last complete cycle to avoid drift
-    player_state.last_turn_regen =
-        player_state.last_turn_regen + (cycles as i64 * TURN_REGEN_INTERVAL);
+    player_state.last_turn_regen += cycles as i64 * TURN_REGEN_INTERVAL;
 
     msg!(
         "Regenerated {} turns for player {}. New total: {}",
@@ -51,6 +63,10 @@ pub fn regenerate_turns(ctx: Context<RegenerateTurns>) -> Result<()> {
 
 /// Reset packs bought counter if a new hour has started (can be called by anyone - crank)
 pub fn reset_packs_if_new_hour(ctx: Context<ResetPacksIfNewHour>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
     let player_state = &mut ctx.accounts.player_state;
     let clock = Clock::get()?;
 
@@ -69,19 +85,25 @@ pub fn reset_packs_if_new_hour(ctx: Context<ResetPacksIfNewHour>) -> Result<()>
     Ok(())
 }
 
-/// Reveal leaderboard after reveal timestamp (computes and stores top 20 players)
+/// Reveal leaderboard after reveal timestamp (computes and stores top N players)
 pub fn reveal_leaderboard(ctx: Context<RevealLeaderboard>) -> Result<()> {
-    let game_round = &ctx.accounts.game_round;
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round = &mut ctx.accounts.game_round;
+    let rules = &ctx.accounts.round_rules;
     let leaderboard = &mut ctx.accounts.leaderboard;
     let clock = Clock::get()?;
 
-    require!(game_round.is_active, PandaBattleError::RoundNotActive);
+    game_round.sync_status(clock.unix_timestamp);
+    require!(game_round.is_open(), PandaBattleError::RoundNotActive);
     require!(
         clock.unix_timestamp >= game_round.leaderboard_reveal_ts,
         PandaBattleError::LeaderboardNotReady
     );
     require!(
-        !leaderboard.is_revealed,
+        game_round.status != RoundStatus::Revealed,
         PandaBattleError::LeaderboardAlreadyRevealed
     );
 
@@ -93,6 +115,10 @@ pub fn reveal_leaderboard(ctx: Context<RevealLeaderboard>) -> Result<()> {
         let data = account_info.try_borrow_data()?;
         let mut data_slice: &[u8] = &data;
         let player_state = PlayerState::try_deserialize(&mut data_slice)?;
+        require!(
+            player_state.is_current(),
+            PandaBattleError::UnsupportedAccountVersion
+        );
 
         // Verify player belongs to this round
         require_eq!(
@@ -105,18 +131,18 @@ pub fn reveal_leaderboard(ctx: Context<RevealLeaderboard>) -> Result<()> {
     }
 
     // Sort by points descending
-    player_data.sort_by(|a, b| b.1.cmp(&a.1));
+    player_data.sort_by_key(|(_, points)| std::cmp::Reverse(*points));
 
-    // Take top 20
-    let top_20: Vec<LeaderboardEntry> = player_data
+    // Take top N (leaderboard size from round rules)
+    let top_entries: Vec<LeaderboardEntry> = player_data
         .into_iter()
-        .take(20)
+        .take(rules.leaderboard_size as usize)
         .map(|(player, points)| LeaderboardEntry { player, points })
         .collect();
 
-    leaderboard.entries = top_20;
+    leaderboard.entries = top_entries;
     leaderboard.is_revealed = true;
-    leaderboard.round = game_round.key();
+    game_round.transition_to(RoundStatus::Revealed)?;
 
     msg!(
         "Leaderboard revealed with {} entries",
@@ -126,15 +152,18 @@ pub fn reveal_leaderboard(ctx: Context<RevealLeaderboard>) -> Result<()> {
     Ok(())
 }
 
-/// Hourly jackpot distribution (post-reveal, weighted random pick from top 20)
+/// Hourly jackpot distribution (post-reveal, weighted random pick from leaderboard)
 pub fn hourly_jackpot(ctx: Context<HourlyJackpot>, client_seed: u8) -> Result<()> {
-    let game_round = &ctx.accounts.game_round;
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round = &mut ctx.accounts.game_round;
     let leaderboard = &ctx.accounts.leaderboard;
     let clock = Clock::get()?;
 
-    require!(game_round.is_active, PandaBattleError::RoundNotActive);
     require!(
-        leaderboard.is_revealed,
+        game_round.status == RoundStatus::Revealed,
         PandaBattleError::LeaderboardNotRevealed
     );
     require!(
@@ -146,6 +175,13 @@ pub fn hourly_jackpot(ctx: Context<HourlyJackpot>, client_seed: u8) -> Result<()
         PandaBattleError::EmptyLeaderboard
     );
 
+    let current_hour = game_round.hours_since_start(clock.unix_timestamp);
+    require!(
+        game_round.last_jackpot_hour != Some(current_hour),
+        PandaBattleError::JackpotAlreadyPaid
+    );
+    game_round.last_jackpot_hour = Some(current_hour);
+
     // For now, we'll use a simple pseudo-random approach
     // In production, this should use VRF similar to battles
     // Using client_seed + clock as entropy source
@@ -154,7 +190,7 @@ pub fn hourly_jackpot(ctx: Context<HourlyJackpot>, client_seed: u8) -> Result<()
         .wrapping_mul(clock.slot);
 
     // Calculate weighted random selection (inverse rank odds)
-    // Rank 1 gets weight 20, rank 2 gets 19, etc.
+    // Rank 1 gets weight N, rank 2 gets N-1, etc.
     let total_weight: u64 = (1..=leaderboard.entries.len() as u64).sum();
     let random_value = entropy % total_weight;
 
@@ -179,25 +215,40 @@ pub fn hourly_jackpot(ctx: Context<HourlyJackpot>, client_seed: u8) -> Result<()
         .checked_div(10000)
         .ok_or(PandaBattleError::Overflow)?;
 
-    // Transfer jackpot to winner
-    let global_config = &ctx.accounts.global_config;
-    let global_config_key = global_config.key();
-    let vault_seeds = &[
-        VAULT_SEED,
-        global_config_key.as_ref(),
-        &[global_config.vault_bump],
-    ];
-    let signer_seeds = &[&vault_seeds[..]];
-
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.winner_token_account.to_account_info(),
-        authority: ctx.accounts.vault.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    require!(
+        ctx.accounts.winner.key() == leaderboard.entries[winner_index].player,
+        PandaBattleError::Unauthorized
+    );
 
-    token::transfer(cpi_ctx, jackpot_amount)?;
+    // Jackpots are paid out of the prize pool held in the round's vault
+    game_round.prize_pool = game_round
+        .prize_pool
+        .checked_sub(jackpot_amount)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+        &[game_round.bump],
+    ]];
+
+    RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .pay_out(
+        &ctx.accounts.winner.to_account_info(),
+        ctx.accounts.winner_token_account.as_ref(),
+        &game_round.to_account_info(),
+        signer_seeds,
+        jackpot_amount,
+    )?;
 
     msg!(
         "Hourly jackpot: {} tokens to winner {} (rank {})",
@@ -218,20 +269,25 @@ pub struct RegenerateTurns<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
     #[account(
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub game_round: Account<'info, GameRound>,
 
+    #[account(address = game_round.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
+
     #[account(
         mut,
         seeds = [
@@ -239,7 +295,8 @@ pub struct RegenerateTurns<'info> {
             game_round.key().as_ref(),
             player_state.player.as_ref()
         ],
-        bump = player_state.bump
+        bump = player_state.bump,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub player_state: Account<'info, PlayerState>,
 }
@@ -251,17 +308,19 @@ pub struct ResetPacksIfNewHour<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
     #[account(
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub game_round: Account<'info, GameRound>,
 
@@ -272,7 +331,8 @@ pub struct ResetPacksIfNewHour<'info> {
             game_round.key().as_ref(),
             player_state.player.as_ref()
         ],
-        bump = player_state.bump
+        bump = player_state.bump,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub player_state: Account<'info, PlayerState>,
 }
@@ -280,49 +340,55 @@ pub struct ResetPacksIfNewHour<'info> {
 #[derive(Accounts)]
 pub struct RevealLeaderboard<'info> {
     /// Anyone can call this (crank)
-    #[account(mut)]
     pub caller: Signer<'info>,
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
     #[account(
+        mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub game_round: Account<'info, GameRound>,
 
+    #[account(address = game_round.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
+
+    /// Created and delegated with the round
     #[account(
-        init_if_needed,
-        payer = caller,
-        space = 8 + Leaderboard::INIT_SPACE,
+        mut,
         seeds = [
             LEADERBOARD_SEED,
             game_round.key().as_ref()
         ],
-        bump
+        bump = leaderboard.bump
     )]
     pub leaderboard: Account<'info, Leaderboard>,
-
-    pub system_program: Program<'info, System>,
     // remaining_accounts: Vec<PlayerState> accounts to scan
 }
 
 #[derive(Accounts)]
 pub struct HourlyJackpot<'info> {
-    /// Anyone can call this (crank)
-    pub caller: Signer<'info>,
+    /// Privileged crank: the client seed picks the winner, so only the operator may call it
+    #[account(
+        constraint = global_config.is_operator(&operator.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub operator: Signer<'info>,
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -330,10 +396,11 @@ pub struct HourlyJackpot<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub game_round: Account<'info, GameRound>,
 
@@ -346,143 +413,248 @@ pub struct HourlyJackpot<'info> {
     )]
     pub leaderboard: Account<'info, Leaderboard>,
 
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
+    #[account(
+        mut,
+        constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
     #[account(
         mut,
-        seeds = [VAULT_SEED, global_config.key().as_ref()],
-        bump = global_config.vault_bump
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub sol_vault: Option<Account<'info, SolVault>>,
 
-    /// Winner's token account (derived from leaderboard selection)
+    /// CHECK: Winner's wallet, checked against the leaderboard selection (receives SOL jackpots)
     #[account(mut)]
-    pub winner_token_account: Account<'info, TokenAccount>,
+    pub winner: UncheckedAccount<'info>,
+
+    /// Winner's token account (token rounds only)
+    #[account(
+        mut,
+        constraint = winner_token_account.owner == winner.key() @ PandaBattleError::Unauthorized,
+        constraint = winner_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub winner_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Cancel a round that missed its minimum-players deadline so players can claim
+/// refunds (can be called by anyone - crank)
+pub fn cancel_underfilled_round(ctx: Context<CancelUnderfilledRound>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round = &mut ctx.accounts.game_round;
+    let clock = Clock::get()?;
+
+    require!(
+        game_round.is_underfilled(clock.unix_timestamp),
+        PandaBattleError::MinPlayersReached
+    );
 
-    pub token_program: Program<'info, Token>,
+    fold_pending_deposits(game_round, &ctx.accounts.pending_deposits)?;
+    game_round.cancel(clock.unix_timestamp)?;
+
+    msg!(
+        "Round {} cancelled: {} of {} required players. Refundable pool: {}",
+        game_round.round_number,
+        game_round.player_count,
+        game_round.min_players,
+        game_round.prize_pool
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelUnderfilledRound<'info> {
+    /// Anyone can call this (crank)
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status.can_transition_to(RoundStatus::Cancelled)
+            @ PandaBattleError::InvalidStatusTransition
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// CHECK: PDA checked by seeds; folded into the pool in the handler if it exists
+    #[account(
+        seeds = [PENDING_DEPOSITS_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub pending_deposits: UncheckedAccount<'info>,
 }
 
 /// Distribute prizes after round ends (calculates prize shares for all players)
 pub fn distribute_prizes(ctx: Context<DistributePrizes>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
     let game_round = &mut ctx.accounts.game_round;
     let leaderboard = &ctx.accounts.leaderboard;
+    let rules = &ctx.accounts.round_rules;
 
-    require!(!game_round.is_active, PandaBattleError::RoundNotEnded);
     require!(
-        !game_round.payouts_processed,
-        PandaBattleError::AlreadyClaimed
+        game_round.status == RoundStatus::Ended,
+        PandaBattleError::RoundNotEnded
     );
     require!(
         leaderboard.is_revealed,
         PandaBattleError::LeaderboardNotRevealed
     );
+    // Underfilled rounds are refunded via `cancel_underfilled_round` instead
+    require!(
+        !game_round.is_underfilled(Clock::get()?.unix_timestamp),
+        PandaBattleError::MinPlayersNotReached
+    );
+
+    // Every player state of the round must be passed exactly once: the rest pool is split
+    // by how many are passed, and a missing player would never get a share
+    let mut player_states: Vec<(&AccountInfo, PlayerState)> =
+        Vec::with_capacity(ctx.remaining_accounts.len());
+    for account_info in ctx.remaining_accounts.iter() {
+        require_keys_eq!(
+            *account_info.owner,
+            crate::ID,
+            PandaBattleError::InvalidRound
+        );
+        let player_state = PlayerState::try_deserialize(&mut &account_info.try_borrow_data()?[..])?;
+        // Player states at an older layout would have their shares written into the wrong fields
+        require!(
+            player_state.is_current(),
+            PandaBattleError::UnsupportedAccountVersion
+        );
+        require_keys_eq!(
+            player_state.round,
+            game_round.key(),
+            PandaBattleError::InvalidRound
+        );
+        require!(
+            !player_states
+                .iter()
+                .any(|(other, _)| other.key == account_info.key),
+            PandaBattleError::PlayerStatesMismatch
+        );
+        player_states.push((account_info, player_state));
+    }
+    require!(
+        player_states.len() == game_round.player_count as usize,
+        PandaBattleError::PlayerStatesMismatch
+    );
+
+    fold_pending_deposits(game_round, &ctx.accounts.pending_deposits)?;
 
     let total_prize_pool = game_round.prize_pool;
-    let top20_pool = total_prize_pool
-        .checked_mul(80)
+    let top_pool = total_prize_pool
+        .checked_mul(rules.top_pool_pct as u64)
         .ok_or(PandaBattleError::Overflow)?
         .checked_div(100)
         .ok_or(PandaBattleError::Overflow)?;
     let rest_pool = total_prize_pool
-        .checked_sub(top20_pool)
+        .checked_sub(top_pool)
         .ok_or(PandaBattleError::Underflow)?;
 
-    // Calculate total points for top 20
-    let total_top20_points: u64 = leaderboard.entries.iter().map(|e| e.points as u64).sum();
-
-    // Process top 20 players (proportional to points)
-    for entry in leaderboard.entries.iter() {
-        // Find the player state in remaining accounts
-        for account_info in ctx.remaining_accounts.iter() {
-            let data = account_info.try_borrow_data()?;
-            let mut data_slice: &[u8] = &data;
+    // Calculate total points for leaderboard players
+    let total_top_points: u64 = leaderboard.entries.iter().map(|e| e.points as u64).sum();
+
+    // Remaining players split the rest pool equally
+    let rest_player_count = player_states
+        .iter()
+        .filter(|(_, player_state)| {
+            !leaderboard
+                .entries
+                .iter()
+                .any(|e| e.player == player_state.player)
+        })
+        .count() as u32;
+    let equal_share = if rest_player_count > 0 {
+        rest_pool
+            .checked_div(rest_player_count as u64)
+            .ok_or(PandaBattleError::Overflow)?
+    } else {
+        0
+    };
 
-            // Try to deserialize as PlayerState
-            if let Ok(mut player_state) = PlayerState::try_deserialize(&mut data_slice) {
-                if player_state.player == entry.player {
-                    // Calculate proportional share
-                    let share = if total_top20_points > 0 {
-                        top20_pool
-                            .checked_mul(entry.points as u64)
-                            .ok_or(PandaBattleError::Overflow)?
-                            .checked_div(total_top20_points)
-                            .ok_or(PandaBattleError::Overflow)?
-                    } else {
-                        0
-                    };
-
-                    player_state.prize_share = share;
-
-                    // Serialize back
-                    drop(data);
-                    let mut data = account_info.try_borrow_mut_data()?;
-                    let mut data_slice: &mut [u8] = &mut data;
-                    player_state.try_serialize(&mut data_slice)?;
-
-                    msg!(
-                        "Top 20 player {} allocated {} tokens ({} points)",
-                        entry.player,
-                        share,
-                        entry.points
-                    );
-                    break;
-                }
+    for (account_info, mut player_state) in player_states {
+        match leaderboard
+            .entries
+            .iter()
+            .find(|e| e.player == player_state.player)
+        {
+            // Leaderboard players get a share proportional to their points
+            Some(entry) => {
+                player_state.prize_share = if total_top_points > 0 {
+                    top_pool
+                        .checked_mul(entry.points as u64)
+                        .ok_or(PandaBattleError::Overflow)?
+                        .checked_div(total_top_points)
+                        .ok_or(PandaBattleError::Overflow)?
+                } else {
+                    0
+                };
+
+                msg!(
+                    "Leaderboard player {} allocated {} tokens ({} points)",
+                    entry.player,
+                    player_state.prize_share,
+                    entry.points
+                );
             }
-        }
-    }
-
-    // Calculate equal share for remaining players
-    let top20_players: Vec<Pubkey> = leaderboard.entries.iter().map(|e| e.player).collect();
-    let mut rest_player_count = 0u32;
-
-    // Count non-top20 players
-    for account_info in ctx.remaining_accounts.iter() {
-        let data = account_info.try_borrow_data()?;
-        let mut data_slice: &[u8] = &data;
-
-        if let Ok(player_state) = PlayerState::try_deserialize(&mut data_slice) {
-            if !top20_players.contains(&player_state.player) {
-                rest_player_count += 1;
+            None => {
+                player_state.prize_share = equal_share;
+
+                msg!(
+                    "Rest player {} allocated {} tokens (equal share)",
+                    player_state.player,
+                    equal_share
+                );
             }
         }
-    }
-
-    // Distribute equal share to rest
-    if rest_player_count > 0 {
-        let equal_share = rest_pool
-            .checked_div(rest_player_count as u64)
-            .ok_or(PandaBattleError::Overflow)?;
-
-        for account_info in ctx.remaining_accounts.iter() {
-            let data = account_info.try_borrow_data()?;
-            let mut data_slice: &[u8] = &data;
 
-            if let Ok(mut player_state) = PlayerState::try_deserialize(&mut data_slice) {
-                if !top20_players.contains(&player_state.player) {
-                    player_state.prize_share = equal_share;
-
-                    // Serialize back
-                    drop(data);
-                    let mut data = account_info.try_borrow_mut_data()?;
-                    let mut data_slice: &mut [u8] = &mut data;
-                    player_state.try_serialize(&mut data_slice)?;
-
-                    msg!(
-                        "Rest player {} allocated {} tokens (equal share)",
-                        player_state.player,
-                        equal_share
-                    );
-                }
-            }
-        }
+        // Serialize back
+        let mut data = account_info.try_borrow_mut_data()?;
+        let mut data_slice: &mut [u8] = &mut data;
+        player_state.try_serialize(&mut data_slice)?;
     }
 
-    // Mark payouts as processed
-    game_round.payouts_processed = true;
+    // Mark payouts as processed; the claim window starts now
+    game_round.transition_to(RoundStatus::Distributed)?;
+    game_round.open_claim_window(Clock::get()?.unix_timestamp);
 
     msg!(
-        "Prize distribution complete. Top 20 pool: {}, Rest pool: {}, Rest players: {}",
-        top20_pool,
+        "Prize distribution complete. Top pool: {}, Rest pool: {}, Rest players: {}, Sponsored: {}",
+        top_pool,
         rest_pool,
-        rest_player_count
+        rest_player_count,
+        game_round.sponsored_amount
     );
 
     Ok(())
@@ -495,7 +667,8 @@ pub struct DistributePrizes<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -503,15 +676,18 @@ pub struct DistributePrizes<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
         bump = game_round.bump,
-        constraint = !game_round.is_active @ PandaBattleError::RoundNotEnded,
-        constraint = !game_round.payouts_processed @ PandaBattleError::AlreadyClaimed
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status == RoundStatus::Ended @ PandaBattleError::RoundNotEnded
     )]
     pub game_round: Account<'info, GameRound>,
 
+    #[account(address = game_round.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
+
     #[account(
         seeds = [
             LEADERBOARD_SEED,
@@ -522,6 +698,1125 @@ pub struct DistributePrizes<'info> {
     )]
     pub leaderboard: Account<'info, Leaderboard>,
 
+    /// CHECK: PDA checked by seeds; folded into the pool in the handler if it exists
+    #[account(
+        seeds = [PENDING_DEPOSITS_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub pending_deposits: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: every PlayerState of the round, each once, to update with prize shares
+}
+
+/// Commit a settled round's ER state and hand its accounts back to the base layer
+/// (can be called by anyone - crank). Runs on the ephemeral rollup once prizes are
+/// distributed or the round is cancelled, so base-layer claims see the final shares.
+/// PlayerStates passed as remaining accounts are undelegated in batches; the round
+/// goes with the last batch (`undelegate_round`), together with the leaderboard.
+pub fn settle_round<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleRound<'info>>,
+    undelegate_round: bool,
+) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round = &ctx.accounts.game_round;
+    require!(game_round.is_settled(), PandaBattleError::RoundNotSettled);
+
+    let mut accounts_to_settle = round_player_states(game_round.key(), ctx.remaining_accounts)?;
+    let player_count = accounts_to_settle.len();
+
+    // Undelegated accounts can't be written again in the ER, so flush pending changes first
+    let game_round_info = ctx.accounts.game_round.to_account_info();
+    let leaderboard_info = ctx.accounts.leaderboard.to_account_info();
+    if undelegate_round {
+        ctx.accounts.game_round.exit(&crate::ID)?;
+        ctx.accounts.leaderboard.exit(&crate::ID)?;
+        accounts_to_settle.push(&game_round_info);
+        accounts_to_settle.push(&leaderboard_info);
+    }
+
+    require!(
+        !accounts_to_settle.is_empty(),
+        PandaBattleError::NothingToSettle
+    );
+
+    ephemeral_rollups_sdk::ephem::commit_and_undelegate_accounts(
+        &ctx.accounts.payer,
+        accounts_to_settle,
+        &ctx.accounts.magic_context,
+        &ctx.accounts.magic_program,
+    )?;
+
+    msg!(
+        "Round {} settling: {} player states undelegated{}",
+        ctx.accounts.game_round.round_number,
+        player_count,
+        if undelegate_round {
+            ", round and leaderboard committed to the base layer"
+        } else {
+            ""
+        }
+    );
+
+    Ok(())
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct SettleRound<'info> {
+    /// Anyone can call this (crank); pays for the commit
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.is_settled() @ PandaBattleError::RoundNotSettled
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    // remaining_accounts: Vec<PlayerState> accounts to undelegate
+}
+
+/// Commit the round's current ER state to the base layer without undelegating it
+/// (can be called by anyone - crank), so observers get a checkpoint between the
+/// periodic commits, e.g. right at the leaderboard reveal. The leaderboard and the
+/// PlayerStates passed as remaining accounts are committed with the round.
+pub fn commit_round_state<'info>(
+    ctx: Context<'_, '_, '_, 'info, CommitRoundState<'info>>,
+) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round_info = ctx.accounts.game_round.to_account_info();
+    let leaderboard_info = ctx.accounts.leaderboard.to_account_info();
+
+    let mut accounts_to_commit =
+        round_player_states(ctx.accounts.game_round.key(), ctx.remaining_accounts)?;
+    let player_count = accounts_to_commit.len();
+    accounts_to_commit.push(&game_round_info);
+    accounts_to_commit.push(&leaderboard_info);
+
+    ephemeral_rollups_sdk::ephem::commit_accounts(
+        &ctx.accounts.payer,
+        accounts_to_commit,
+        &ctx.accounts.magic_context,
+        &ctx.accounts.magic_program,
+    )?;
+
+    msg!(
+        "Round {} state and leaderboard committed with {} player states",
+        ctx.accounts.game_round.round_number,
+        player_count
+    );
+
+    Ok(())
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct CommitRoundState<'info> {
+    /// Anyone can call this (crank); pays for the commit
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    // remaining_accounts: Vec<PlayerState> accounts to commit
+}
+
+/// PlayerStates of `round` passed as remaining accounts, for committing to the base layer
+fn round_player_states<'a, 'info>(
+    round: Pubkey,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<Vec<&'a AccountInfo<'info>>> {
+    remaining_accounts
+        .iter()
+        .map(|account_info| {
+            let data = account_info.try_borrow_data()?;
+            let mut data_slice: &[u8] = &data;
+            let player_state = PlayerState::try_deserialize(&mut data_slice)?;
+            require_keys_eq!(player_state.round, round, PandaBattleError::InvalidRound);
+            Ok(account_info)
+        })
+        .collect()
+}
+
+/// Start the arena's next round from its rollover template once the current one has ended
+/// (can be called by anyone - crank). The caller fronts rent and delegation costs
+/// and is reimbursed from the rollover vault in the same transaction.
+pub fn start_next_round(ctx: Context<StartNextRound>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let accounts = &mut *ctx.accounts;
+    accounts.game_round.bump = ctx.bumps.game_round;
+
+    // Delegated rounds are owned by the delegation program on the base layer; their data
+    // is the last ER commit, so commit the ended round first (`commit_round_state`)
+    let previous_round =
+        GameRound::try_deserialize(&mut &accounts.previous_round.try_borrow_data()?[..])?;
+    require!(
+        previous_round.is_current(),
+        PandaBattleError::UnsupportedAccountVersion
+    );
+    require!(
+        !previous_round.is_open(),
+        PandaBattleError::RoundStillActive
+    );
+    require!(
+        !accounts.arena.rollover_requires_distribution || previous_round.is_settled(),
+        PandaBattleError::PayoutsNotProcessed
+    );
+
+    let mint = prepare_round_vault(
+        accounts.round_template.currency,
+        accounts.game_round.key(),
+        accounts.mint.as_ref(),
+        accounts.vault.as_ref(),
+        accounts.sol_vault.as_mut(),
+        ctx.bumps.sol_vault,
+    )?;
+
+    let params = accounts.round_template.params;
+    open_round(
+        &mut accounts.arena,
+        &mut accounts.game_round,
+        &accounts.round_rules,
+        mint,
+        accounts.round_template.key(),
+        &params,
+        None,
+    )?;
+    // The rollover vault reimburses the caller, so it gets the rent back on close
+    accounts.game_round.rent_payer = accounts.rollover_vault.key();
+    accounts.leaderboard.round = accounts.game_round.key();
+    accounts.leaderboard.bump = ctx.bumps.leaderboard;
+
+    accounts.round_template.rounds_created = accounts
+        .round_template
+        .rounds_created
+        .checked_add(1)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    // Rent for the accounts the caller just funded, plus whatever delegation charges
+    let vault_rent = match accounts.round_template.currency {
+        Currency::Token => accounts
+            .vault
+            .as_ref()
+            .map(|v| v.to_account_info().lamports()),
+        Currency::NativeSol => accounts
+            .sol_vault
+            .as_ref()
+            .map(|v| v.to_account_info().lamports()),
+    }
+    .unwrap_or_default();
+    let rent_paid = accounts
+        .game_round
+        .to_account_info()
+        .lamports()
+        .checked_add(accounts.leaderboard.to_account_info().lamports())
+        .and_then(|rent| rent.checked_add(vault_rent))
+        .ok_or(PandaBattleError::Overflow)?;
+    let caller_before_delegation = accounts.caller.lamports();
+
+    delegate_new_round(
+        &accounts.game_round,
+        ephemeral_rollups_sdk::cpi::DelegateAccounts {
+            payer: &accounts.caller.to_account_info(),
+            pda: &accounts.game_round.to_account_info(),
+            owner_program: &accounts.owner_program.to_account_info(),
+            buffer: &accounts.buffer_account.to_account_info(),
+            delegation_record: &accounts.delegation_record_account.to_account_info(),
+            delegation_metadata: &accounts.delegation_metadata_account.to_account_info(),
+            delegation_program: &accounts.delegation_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+    )?;
+    delegate_new_leaderboard(
+        &accounts.game_round,
+        &accounts.leaderboard,
+        ephemeral_rollups_sdk::cpi::DelegateAccounts {
+            payer: &accounts.caller.to_account_info(),
+            pda: &accounts.leaderboard.to_account_info(),
+            owner_program: &accounts.owner_program.to_account_info(),
+            buffer: &accounts.leaderboard_buffer_account.to_account_info(),
+            delegation_record: &accounts
+                .leaderboard_delegation_record_account
+                .to_account_info(),
+            delegation_metadata: &accounts
+                .leaderboard_delegation_metadata_account
+                .to_account_info(),
+            delegation_program: &accounts.delegation_program.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+    )?;
+
+    let delegation_paid = caller_before_delegation.saturating_sub(accounts.caller.lamports());
+    let reimbursement = rent_paid
+        .checked_add(delegation_paid)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    // Never dip into the vault's own rent-exempt reserve
+    let vault_info = accounts.rollover_vault.to_account_info();
+    let reserve = Rent::get()?.minimum_balance(vault_info.data_len());
+    let available = vault_info.lamports().saturating_sub(reserve);
+    require!(
+        available >= reimbursement,
+        PandaBattleError::InsufficientRolloverFunds
+    );
+
+    **vault_info.try_borrow_mut_lamports()? -= reimbursement;
+    **accounts
+        .caller
+        .to_account_info()
+        .try_borrow_mut_lamports()? += reimbursement;
+
+    accounts.rollover_vault.total_reimbursed = accounts
+        .rollover_vault
+        .total_reimbursed
+        .checked_add(reimbursement)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    msg!(
+        "Round {} rolled over into round {} from template {}. Reimbursed {} lamports",
+        previous_round.round_number,
+        accounts.game_round.round_number,
+        accounts.round_template.template_id,
+        reimbursement
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct StartNextRound<'info> {
+    /// Anyone can call this (crank); reimbursed from the rollover vault
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            ARENA_SEED,
+            global_config.key().as_ref(),
+            arena.arena_id.to_le_bytes().as_ref()
+        ],
+        bump = arena.bump
+    )]
+    pub arena: Account<'info, Arena>,
+
+    /// CHECK: The round `arena.current_round` points at, read in the handler. It may
+    /// still be delegated, so it is owned by this program or the delegation program.
+    #[account(
+        seeds = [
+            GAME_ROUND_SEED,
+            arena.key().as_ref(),
+            arena.current_round.to_le_bytes().as_ref()
+        ],
+        bump,
+        constraint = *previous_round.owner == crate::ID
+            || *previous_round.owner == ::ephemeral_rollups_sdk::id()
+            @ PandaBattleError::InvalidRound
+    )]
+    pub previous_round: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            ROUND_TEMPLATE_SEED,
+            global_config.key().as_ref(),
+            round_template.template_id.to_le_bytes().as_ref()
+        ],
+        bump = round_template.bump,
+        constraint = arena.rollover_template == Some(round_template.key())
+            @ PandaBattleError::RolloverDisabled,
+        // `update_round_template` may have moved the template to another mint
+        constraint = round_template.fits_arena(&arena) @ PandaBattleError::InvalidMint
+    )]
+    pub round_template: Account<'info, RoundTemplate>,
+
+    #[account(address = round_template.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
+
+    /// Token mint for the next round (token templates only)
+    #[account(address = arena.token_mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + GameRound::INIT_SPACE,
+        seeds = [
+            GAME_ROUND_SEED,
+            arena.key().as_ref(),
+            (arena.total_rounds + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// Token vault for the next round (token templates only)
+    #[account(
+        init,
+        payer = caller,
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for the next round (native SOL templates only)
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + SolVault::INIT_SPACE,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    /// Leaderboard for this round, delegated with it so the reveal runs in the rollup
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    #[account(
+        mut,
+        seeds = [ROLLOVER_VAULT_SEED, global_config.key().as_ref()],
+        bump = rollover_vault.bump
+    )]
+    pub rollover_vault: Account<'info, RolloverVault>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// CHECK: The buffer account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::BUFFER, game_round.key().as_ref()],
+        bump,
+        seeds::program = crate::id()
+    )]
+    pub buffer_account: AccountInfo<'info>,
+
+    /// CHECK: The delegation record account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_RECORD, game_round.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub delegation_record_account: AccountInfo<'info>,
+
+    /// CHECK: The delegation metadata account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_METADATA, game_round.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub delegation_metadata_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's buffer account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::BUFFER, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = crate::id()
+    )]
+    pub leaderboard_buffer_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's delegation record account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_RECORD, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub leaderboard_delegation_record_account: AccountInfo<'info>,
+
+    /// CHECK: The leaderboard's delegation metadata account
+    #[account(
+        mut,
+        seeds = [ephemeral_rollups_sdk::consts::DELEGATION_METADATA, leaderboard.key().as_ref()],
+        bump,
+        seeds::program = delegation_program.key()
+    )]
+    pub leaderboard_delegation_metadata_account: AccountInfo<'info>,
+
+    /// CHECK: The owner program of the pda
+    #[account(address = crate::id())]
+    pub owner_program: AccountInfo<'info>,
+
+    /// CHECK: The delegation program
+    #[account(address = ::ephemeral_rollups_sdk::id())]
+    pub delegation_program: AccountInfo<'info>,
+}
+
+/// Sweep prizes and refunds left unclaimed after the claim window (can be called by
+/// anyone - crank). Funds go into the arena's next round prize pool while it is still
+/// open, otherwise to the treasury; unwithdrawn rake stays in the vault. PlayerStates
+/// passed as remaining accounts are marked expired; later calls only mark.
+pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let accounts = &mut *ctx.accounts;
+    let game_round = &mut accounts.game_round;
+    let clock = Clock::get()?;
+
+    require!(game_round.is_settled(), PandaBattleError::RoundNotSettled);
+    require!(
+        game_round.claims_expired(clock.unix_timestamp),
+        PandaBattleError::ClaimWindowOpen
+    );
+
+    if !game_round.unclaimed_swept {
+        // An open next round is normally still delegated, and the base layer can't credit a
+        // delegated account, so the funds are queued in its `PendingDeposits` and folded
+        // into its pool when it settles; without an open next round they go to the treasury
+        let next_info = accounts.next_round.to_account_info();
+        let next_round = if *next_info.owner == crate::ID
+            || *next_info.owner == ::ephemeral_rollups_sdk::id()
+        {
+            let next = GameRound::try_deserialize(&mut &next_info.try_borrow_data()?[..])?;
+            require!(
+                next.is_current(),
+                PandaBattleError::UnsupportedAccountVersion
+            );
+            (next.is_open() && next.currency == game_round.currency && next.mint == game_round.mint)
+                .then_some(next)
+        } else {
+            None
+        };
+
+        let round_number_bytes = game_round.round_number.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            round_number_bytes.as_ref(),
+            &[game_round.bump],
+        ]];
+
+        let vault = RoundVault {
+            currency: game_round.currency,
+            mint: accounts.mint.as_ref(),
+            vault: accounts.vault.as_mut(),
+            sol_vault: accounts.sol_vault.as_ref(),
+            token_program: &accounts.token_program,
+            system_program: &accounts.system_program,
+        };
+        let unclaimed = vault.balance()?.saturating_sub(game_round.protocol_fees);
+
+        match next_round {
+            Some(next_round) if unclaimed > 0 => {
+                let received = match game_round.currency {
+                    Currency::Token => {
+                        let next_vault = accounts
+                            .next_vault
+                            .as_mut()
+                            .ok_or(PandaBattleError::InvalidCurrency)?;
+                        let before = next_vault.amount;
+                        vault.pay_out(
+                            &next_info,
+                            Some(next_vault),
+                            &game_round.to_account_info(),
+                            signer_seeds,
+                            unclaimed,
+                        )?;
+                        next_vault.reload()?;
+                        next_vault.amount.saturating_sub(before)
+                    }
+                    Currency::NativeSol => {
+                        let next_sol_vault = accounts
+                            .next_sol_vault
+                            .as_ref()
+                            .ok_or(PandaBattleError::InvalidCurrency)?;
+                        require_keys_eq!(
+                            next_sol_vault.game_round,
+                            next_info.key(),
+                            PandaBattleError::Unauthorized
+                        );
+                        vault.pay_out(
+                            &next_sol_vault.to_account_info(),
+                            None,
+                            &game_round.to_account_info(),
+                            signer_seeds,
+                            unclaimed,
+                        )?;
+                        unclaimed
+                    }
+                };
+
+                let pending = accounts
+                    .next_pending_deposits
+                    .as_mut()
+                    .ok_or(PandaBattleError::InvalidRound)?;
+                if pending.round == Pubkey::default() {
+                    pending.round = next_info.key();
+                    pending.rent_payer = accounts.caller.key();
+                    pending.bump = ctx.bumps.next_pending_deposits.unwrap_or_default();
+                }
+                pending.carried_over = pending
+                    .carried_over
+                    .checked_add(received)
+                    .ok_or(PandaBattleError::Overflow)?;
+
+                msg!(
+                    "Swept {} unclaimed towards round {} prize pool",
+                    received,
+                    next_round.round_number
+                );
+            }
+            _ if unclaimed > 0 => {
+                vault.pay_out(
+                    &accounts.treasurer.to_account_info(),
+                    accounts.treasury_token_account.as_ref(),
+                    &game_round.to_account_info(),
+                    signer_seeds,
+                    unclaimed,
+                )?;
+
+                msg!("Swept {} unclaimed to the treasury", unclaimed);
+            }
+            _ => {}
+        }
+
+        game_round.unclaimed_swept = true;
+    }
+
+    let mut expired = 0u32;
+    for account_info in ctx.remaining_accounts.iter() {
+        require_keys_eq!(
+            *account_info.owner,
+            crate::ID,
+            PandaBattleError::InvalidRound
+        );
+        let data = account_info.try_borrow_data()?;
+        let mut data_slice: &[u8] = &data;
+        let mut player_state = PlayerState::try_deserialize(&mut data_slice)?;
+        require!(
+            player_state.is_current(),
+            PandaBattleError::UnsupportedAccountVersion
+        );
+        require_keys_eq!(
+            player_state.round,
+            game_round.key(),
+            PandaBattleError::InvalidRound
+        );
+
+        if !player_state.prize_claimed && !player_state.claim_expired {
+            player_state.claim_expired = true;
+
+            drop(data);
+            let mut data = account_info.try_borrow_mut_data()?;
+            let mut data_slice: &mut [u8] = &mut data;
+            player_state.try_serialize(&mut data_slice)?;
+            expired += 1;
+        }
+    }
+
+    msg!(
+        "Round {} claims expired. Marked {} player states expired",
+        game_round.round_number,
+        expired
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SweepUnclaimed<'info> {
+    /// Anyone can call this (crank); funds the next round's `PendingDeposits` if needed
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// CHECK: The arena's next round, read in the handler; its funds are queued while it is
+    /// open. It may not exist yet or still be delegated, so it is owned by the system
+    /// program, this program or the delegation program.
+    #[account(
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            (game_round.round_number + 1).to_le_bytes().as_ref()
+        ],
+        bump,
+        constraint = *next_round.owner == crate::ID
+            || *next_round.owner == ::ephemeral_rollups_sdk::id()
+            || *next_round.owner == System::id()
+            @ PandaBattleError::InvalidRound
+    )]
+    pub next_round: UncheckedAccount<'info>,
+
+    /// Queues the funds for the next round's pool (swept into the next round only)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = 8 + PendingDeposits::INIT_SPACE,
+        seeds = [PENDING_DEPOSITS_SEED, next_round.key().as_ref()],
+        bump
+    )]
+    pub next_pending_deposits: Option<Account<'info, PendingDeposits>>,
+
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The round's vault ATA (token rounds only)
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    /// Next round's vault ATA (token rounds swept into the next round only)
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = next_round,
+        associated_token::token_program = token_program,
+    )]
+    pub next_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Next round's lamport vault (SOL rounds swept into the next round only; its
+    /// `game_round` is checked in the handler)
+    #[account(mut)]
+    pub next_sol_vault: Option<Account<'info, SolVault>>,
+
+    /// CHECK: Receives swept lamports of SOL rounds when there is no open next round
+    #[account(mut, address = global_config.treasurer @ PandaBattleError::Unauthorized)]
+    pub treasurer: UncheckedAccount<'info>,
+
+    /// Treasurer's token account (token rounds swept to the treasury only)
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == global_config.treasurer @ PandaBattleError::Unauthorized,
+        constraint = treasury_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Close a settled player's state once there is nothing left for them to claim
+/// (can be called by anyone - crank). Rent goes back to the player.
+pub fn close_player_state(ctx: Context<ClosePlayerState>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round = &mut ctx.accounts.game_round;
+    let player_state = &ctx.accounts.player_state;
+
+    require!(game_round.is_settled(), PandaBattleError::RoundNotSettled);
+    require!(
+        player_state.has_nothing_to_claim(game_round),
+        PandaBattleError::UnclaimedRewards
+    );
+
+    game_round.open_player_states = game_round
+        .open_player_states
+        .checked_sub(1)
+        .ok_or(PandaBattleError::Underflow)?;
+
+    msg!(
+        "Closed player state of {} in round {}. {} still open",
+        player_state.player,
+        game_round.round_number,
+        game_round.open_player_states
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePlayerState<'info> {
+    /// Anyone can call this (crank)
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    #[account(
+        mut,
+        seeds = [
+            PLAYER_STATE_SEED,
+            game_round.key().as_ref(),
+            player_state.player.as_ref()
+        ],
+        bump = player_state.bump,
+        close = player,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub player_state: Account<'info, PlayerState>,
+
+    /// CHECK: Receives the rent; must be the player who paid it
+    #[account(mut, address = player_state.player @ PandaBattleError::Unauthorized)]
+    pub player: UncheckedAccount<'info>,
+}
+
+/// Close a settled round's sponsor record once there is nothing left to refund from it
+/// (can be called by anyone - crank). Rent goes back to the sponsor.
+pub fn close_sponsor(ctx: Context<CloseSponsor>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let game_round = &mut ctx.accounts.game_round;
+    let sponsor_record = &ctx.accounts.sponsor_record;
+
+    require!(game_round.is_settled(), PandaBattleError::RoundNotSettled);
+    // Distributed sponsorships became prizes; refunds end with the claim window
+    let nothing_to_claim = game_round.status == RoundStatus::Distributed
+        || game_round.unclaimed_swept
+        || game_round.claims_expired(Clock::get()?.unix_timestamp)
+        || game_round.refund_share(sponsor_record.amount) == 0;
+    require!(nothing_to_claim, PandaBattleError::UnclaimedRewards);
+
+    // Sponsorships made after the round's deposits were folded in aren't counted
+    game_round.open_sponsors = game_round.open_sponsors.saturating_sub(1);
+
+    msg!(
+        "Closed sponsor record of {} in round {}. {} still open",
+        sponsor_record.sponsor,
+        game_round.round_number,
+        game_round.open_sponsors
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseSponsor<'info> {
+    /// Anyone can call this (crank)
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    #[account(
+        mut,
+        seeds = [
+            SPONSOR_SEED,
+            game_round.key().as_ref(),
+            sponsor_record.sponsor.as_ref()
+        ],
+        bump = sponsor_record.bump,
+        close = sponsor
+    )]
+    pub sponsor_record: Account<'info, Sponsor>,
+
+    /// CHECK: Receives the rent; must be the sponsor who paid it
+    #[account(mut, address = sponsor_record.sponsor @ PandaBattleError::Unauthorized)]
+    pub sponsor: UncheckedAccount<'info>,
+}
+
+/// Close a settled round once every player state and sponsor record is closed (can be
+/// called by anyone - crank).
+/// Leftover vault funds (unwithdrawn rake and rounding dust) go to the treasurer; the
+/// round, vault and leaderboard rent goes back to whoever paid it.
+pub fn close_round(ctx: Context<CloseRound>) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_CRANKS)?;
+
+    let accounts = &mut *ctx.accounts;
+    let game_round = &mut accounts.game_round;
+
+    require!(game_round.is_settled(), PandaBattleError::RoundNotSettled);
+    require!(
+        game_round.open_player_states == 0,
+        PandaBattleError::PlayerStatesOpen
+    );
+    require!(
+        game_round.open_sponsors == 0,
+        PandaBattleError::SponsorsOpen
+    );
+    // Rollover reads the arena's current round, so keep it while rollover is enabled.
+    // Rounds migrated from the baseline layout have no arena (nor rollover).
+    if game_round.arena != accounts.global_config.key() {
+        let arena = Arena::try_deserialize(&mut &accounts.arena.try_borrow_data()?[..])?;
+        require!(
+            game_round.round_number != arena.current_round || arena.rollover_template.is_none(),
+            PandaBattleError::RolloverPending
+        );
+    }
+    // The account is deleted on exit, so this only checks the lifecycle allows it
+    game_round.transition_to(RoundStatus::Closed)?;
+
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+        &[game_round.bump],
+    ]];
+
+    let swept = RoundVault {
+        currency: game_round.currency,
+        mint: accounts.mint.as_ref(),
+        vault: accounts.vault.as_mut(),
+        sol_vault: accounts.sol_vault.as_ref(),
+        token_program: &accounts.token_program,
+        system_program: &accounts.system_program,
+    }
+    .close(
+        &accounts.treasurer.to_account_info(),
+        accounts.treasury_token_account.as_ref(),
+        &accounts.rent_payer.to_account_info(),
+        &game_round.to_account_info(),
+        signer_seeds,
+    )?;
+
+    // Pending deposits only exist if something was deposited on the base layer
+    let pending_info = accounts.pending_deposits.to_account_info();
+    if pending_info.owner == &crate::ID {
+        let rent_payer = {
+            let data = pending_info.try_borrow_data()?;
+            PendingDeposits::try_deserialize(&mut &data[..])?.rent_payer
+        };
+        let receiver = accounts
+            .pending_deposits_rent_payer
+            .as_ref()
+            .ok_or(PandaBattleError::Unauthorized)?;
+        require_keys_eq!(receiver.key(), rent_payer, PandaBattleError::Unauthorized);
+
+        close_program_account(&pending_info, receiver)?;
+    }
+
+    msg!(
+        "Closed round {}. Swept {} to the treasury",
+        game_round.round_number,
+        swept
+    );
+
+    Ok(())
+}
+
+/// Close an account of this program that isn't loaded as an `Account`, sending its rent
+/// to `receiver`
+fn close_program_account(info: &AccountInfo, receiver: &AccountInfo) -> Result<()> {
+    let rent = info.lamports();
+    **info.try_borrow_mut_lamports()? = 0;
+    **receiver.try_borrow_mut_lamports()? += rent;
+    info.assign(&System::id());
+    info.resize(0)?;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseRound<'info> {
+    /// Anyone can call this (crank)
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: The round's arena, read in the handler; the global config for rounds
+    /// migrated from the baseline layout, whose address it was derived from
+    #[account(owner = crate::ID, address = game_round.arena @ PandaBattleError::InvalidRound)]
+    pub arena: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        close = rent_payer,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    /// CHECK: Receives the round and vault rent; must be whoever paid it
+    #[account(mut, address = game_round.rent_payer @ PandaBattleError::Unauthorized)]
+    pub rent_payer: UncheckedAccount<'info>,
+
+    /// Created with the round, so its rent goes back with the round's
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump = leaderboard.bump,
+        close = rent_payer
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    /// CHECK: PDA checked by seeds; closed in the handler if it was ever created
+    #[account(
+        mut,
+        seeds = [PENDING_DEPOSITS_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub pending_deposits: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the pending deposits rent; checked against `pending_deposits.rent_payer`
+    #[account(mut)]
+    pub pending_deposits_rent_payer: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Receives leftover lamports of SOL rounds
+    #[account(mut, address = global_config.treasurer @ PandaBattleError::Unauthorized)]
+    pub treasurer: UncheckedAccount<'info>,
+
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The round's vault ATA, closed here (token rounds only)
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    /// Treasurer's token account for leftover tokens (token rounds only)
+    #[account(
+        mut,
+        constraint = treasury_token_account.owner == global_config.treasurer @ PandaBattleError::Unauthorized,
+        constraint = treasury_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
     pub system_program: Program<'info, System>,
-    // remaining_accounts: Vec<PlayerState> accounts to update with prize shares
+    pub token_program: Interface<'info, TokenInterface>,
 }
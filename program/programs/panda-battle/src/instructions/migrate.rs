@@ -0,0 +1,437 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::constants::*;
+use crate::errors::PandaBattleError;
+use crate::state::*;
+
+/// Upgrade the global config from the baseline layout (can be called by anyone; the payer
+/// funds the extra rent). Its `token_mint` and round counters moved into arenas, so they
+/// are dropped; the admin also takes the operator and treasurer roles.
+pub fn migrate_global_config(ctx: Context<MigrateGlobalConfig>) -> Result<()> {
+    let old = read_baseline::<GlobalConfig, GlobalConfigV0>(&ctx.accounts.global_config)?;
+
+    let global_config = GlobalConfig {
+        version: GLOBAL_CONFIG_VERSION,
+        admin: old.admin,
+        pending_admin: None,
+        operator: old.admin,
+        treasurer: old.admin,
+        total_arenas: 0,
+        paused_actions: 0,
+        protocol_fee_bps: 0,
+        delegation: DelegationSettings {
+            validator: Some(DEFAULT_ER_VALIDATOR),
+            commit_frequency_ms: DEFAULT_COMMIT_FREQUENCY_MS,
+        },
+        bump: old.bump,
+        vault_bump: old.vault_bump,
+    };
+    write_current(
+        &ctx.accounts.global_config,
+        &global_config,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    msg!(
+        "Global config migrated from v0 to v{}",
+        GLOBAL_CONFIG_VERSION
+    );
+
+    Ok(())
+}
+
+/// Upgrade a game round from the baseline layout (admin only: the baseline layout doesn't
+/// record the round's mint or rules, so the admin supplies them; the admin funds the extra
+/// rent). Delegated rounds must be undelegated first.
+/// The round keeps its address, which was derived from the global config: that key
+/// stands in for its arena. Its vault balance becomes the prize pool (baseline jackpots
+/// were paid from the vault without leaving the pool), and the leaderboard is created if
+/// the round was never revealed.
+pub fn migrate_game_round(ctx: Context<MigrateGameRound>, round_number: u64) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let old = read_baseline::<GameRound, GameRoundV0>(&accounts.game_round)?;
+
+    // The seeds were derived from these fields
+    require_keys_eq!(
+        old.global_config,
+        accounts.global_config.key(),
+        PandaBattleError::InvalidRound
+    );
+    require!(
+        old.round_number == round_number && old.bump == ctx.bumps.game_round,
+        PandaBattleError::InvalidRound
+    );
+    require!(
+        is_baseline_rules(&accounts.round_rules),
+        PandaBattleError::InvalidRules
+    );
+
+    accounts.leaderboard.round = accounts.game_round.key();
+    accounts.leaderboard.bump = ctx.bumps.leaderboard;
+
+    let status = if old.payouts_processed {
+        RoundStatus::Distributed
+    } else if !old.is_active {
+        RoundStatus::Ended
+    } else if accounts.leaderboard.is_revealed {
+        RoundStatus::Revealed
+    } else {
+        RoundStatus::Active
+    };
+
+    let game_round = GameRound {
+        version: GAME_ROUND_VERSION,
+        global_config: old.global_config,
+        arena: old.global_config,
+        currency: Currency::Token,
+        mint: accounts.mint.key(),
+        round_number: old.round_number,
+        entry_fee: old.entry_fee,
+        attack_pack_price: old.attack_pack_price,
+        duration_secs: old.duration_secs,
+        entry_hourly_inc_pct: old.entry_hourly_inc_pct,
+        reroll_fees: [BASELINE_REROLL_FEE; MAX_REROLLS],
+        min_players_deadline: old.start_time,
+        start_time: old.start_time,
+        end_time: old.end_time,
+        leaderboard_reveal_ts: old.leaderboard_reveal_ts,
+        rules: accounts.round_rules.key(),
+        delegation: accounts.global_config.delegation,
+        prize_pool: accounts.vault.amount,
+        players_paid: old.prize_pool,
+        player_count: old.player_count,
+        open_player_states: old.player_count,
+        total_battles: old.total_battles,
+        status,
+        rent_payer: accounts.global_config.admin,
+        bump: old.bump,
+        // Caps, claim windows, rake and sponsors didn't exist yet
+        ..Default::default()
+    };
+    write_current(
+        &accounts.game_round,
+        &game_round,
+        &accounts.admin,
+        &accounts.system_program,
+    )?;
+
+    msg!(
+        "Game round {} migrated from v0 to v{} as {:?}",
+        round_number,
+        GAME_ROUND_VERSION,
+        status
+    );
+
+    Ok(())
+}
+
+/// Upgrade a player state from the baseline layout (can be called by anyone; the payer
+/// funds the extra rent). Delegated player states must be undelegated first.
+/// Pack and reroll spending wasn't tracked, so refunds cover the entry fee only.
+pub fn migrate_player_state(ctx: Context<MigratePlayerState>) -> Result<()> {
+    let old = read_baseline::<PlayerState, PlayerStateV0>(&ctx.accounts.player_state)?;
+
+    // The seeds were derived from these fields
+    require_keys_eq!(
+        old.round,
+        ctx.accounts.game_round.key(),
+        PandaBattleError::InvalidRound
+    );
+    require_keys_eq!(
+        old.player,
+        ctx.accounts.player.key(),
+        PandaBattleError::Unauthorized
+    );
+    require!(
+        old.bump == ctx.bumps.player_state,
+        PandaBattleError::InvalidRound
+    );
+
+    let player_state = PlayerState {
+        version: PLAYER_STATE_VERSION,
+        player: old.player,
+        round: old.round,
+        str: old.str,
+        agi: old.agi,
+        int: old.int,
+        level: old.level,
+        xp: old.xp,
+        points: old.points,
+        turns: old.turns,
+        max_turns: old.max_turns,
+        last_turn_regen: old.last_turn_regen,
+        rerolls_used: old.rerolls_used,
+        packs_bought_hour: old.packs_bought_hour,
+        last_pack_hour: old.last_pack_hour,
+        last_battle: old.last_battle,
+        battles_fought: old.battles_fought,
+        wins: old.wins,
+        losses: old.losses,
+        prize_share: old.prize_share,
+        prize_claimed: old.prize_claimed,
+        claim_expired: false,
+        joined_at: old.joined_at,
+        entry_fee_paid: old.entry_fee_paid,
+        packs_spent: 0,
+        rerolls_spent: 0,
+        bump: old.bump,
+    };
+    write_current(
+        &ctx.accounts.player_state,
+        &player_state,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+
+    msg!(
+        "Player state {} migrated from v0 to v{}",
+        ctx.accounts.player_state.key(),
+        PLAYER_STATE_VERSION
+    );
+
+    Ok(())
+}
+
+// ============== BASELINE LAYOUTS ==============
+
+/// Reroll fee the baseline program charged for every reroll
+const BASELINE_REROLL_FEE: u64 = 1_000_000;
+
+/// Baseline (version 0) global config: a single table with its own mint and round counter
+#[derive(AnchorDeserialize, InitSpace)]
+#[allow(dead_code)] // The mint and counters are dropped, but still part of the layout
+struct GlobalConfigV0 {
+    pub admin: Pubkey,
+    pub token_mint: Pubkey,
+    pub current_round: u64,
+    pub total_rounds: u64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+/// Baseline (version 0) game round, addressed by global config and round number
+#[derive(AnchorDeserialize, InitSpace)]
+struct GameRoundV0 {
+    pub global_config: Pubkey,
+    pub round_number: u64,
+    pub entry_fee: u64,
+    pub attack_pack_price: u64,
+    pub duration_secs: i64,
+    pub entry_hourly_inc_pct: u8,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub leaderboard_reveal_ts: i64,
+    pub prize_pool: u64,
+    pub player_count: u32,
+    pub total_battles: u32,
+    pub is_active: bool,
+    pub payouts_processed: bool,
+    pub bump: u8,
+}
+
+/// Baseline (version 0) player state
+#[derive(AnchorDeserialize, InitSpace)]
+struct PlayerStateV0 {
+    pub player: Pubkey,
+    pub round: Pubkey,
+    pub str: u8,
+    pub agi: u8,
+    pub int: u8,
+    pub level: u8,
+    pub xp: u32,
+    pub points: u16,
+    pub turns: u8,
+    pub max_turns: u8,
+    pub last_turn_regen: i64,
+    pub rerolls_used: u8,
+    pub packs_bought_hour: u8,
+    pub last_pack_hour: i64,
+    pub last_battle: i64,
+    pub battles_fought: u16,
+    pub wins: u16,
+    pub losses: u16,
+    pub prize_share: u64,
+    pub prize_claimed: bool,
+    pub joined_at: i64,
+    pub entry_fee_paid: u64,
+    pub bump: u8,
+}
+
+// `Versioned::version_of` recognises the baseline layouts by these sizes
+const _: () = assert!(GlobalConfigV0::INIT_SPACE == GLOBAL_CONFIG_V0_SPACE);
+const _: () = assert!(GameRoundV0::INIT_SPACE == GAME_ROUND_V0_SPACE);
+const _: () = assert!(PlayerStateV0::INIT_SPACE == PLAYER_STATE_V0_SPACE);
+
+// ============== CONTEXTS ==============
+
+#[derive(Accounts)]
+pub struct MigrateGlobalConfig<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Old layout, so it cannot be deserialized yet; owner, seeds and
+    /// discriminator are checked
+    #[account(mut, owner = crate::ID, seeds = [GLOBAL_CONFIG_SEED], bump)]
+    pub global_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_number: u64)]
+pub struct MigrateGameRound<'info> {
+    #[account(
+        mut,
+        constraint = global_config.is_admin(&admin.key()) @ PandaBattleError::Unauthorized
+    )]
+    pub admin: Signer<'info>,
+
+    /// Migrated first (`migrate_global_config`)
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Old layout, so it cannot be deserialized yet; owner, seeds (baseline
+    /// layout: global config and round number) and discriminator are checked, and the
+    /// stored fields against the seeds
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            GAME_ROUND_SEED,
+            global_config.key().as_ref(),
+            round_number.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub game_round: UncheckedAccount<'info>,
+
+    /// Rules matching the balance constants the baseline program hardcoded
+    #[account(
+        seeds = [
+            ROUND_RULES_SEED,
+            global_config.key().as_ref(),
+            round_rules.rules_id.to_le_bytes().as_ref()
+        ],
+        bump = round_rules.bump
+    )]
+    pub round_rules: Account<'info, RoundRules>,
+
+    /// The baseline global config's token mint
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Baseline rounds only had one once revealed
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePlayerState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Only used to derive the player state's address; checked against its stored round
+    pub game_round: UncheckedAccount<'info>,
+
+    /// CHECK: Only used to derive the player state's address; checked against its stored player
+    pub player: UncheckedAccount<'info>,
+
+    /// CHECK: Old layout, so it cannot be deserialized yet; owner, seeds and discriminator
+    /// are checked, and the stored fields against the seeds
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            PLAYER_STATE_SEED,
+            game_round.key().as_ref(),
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub player_state: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// ============== UTILITY FUNCTIONS ==============
+
+/// Parse a `T` account still at its baseline layout `V`. Current accounts fail with
+/// `AlreadyMigrated`, anything else with `UnsupportedAccountVersion`.
+fn read_baseline<T: Versioned, V: AnchorDeserialize>(account: &AccountInfo) -> Result<V> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.starts_with(T::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    match T::version_of(&data)? {
+        0 => Ok(V::deserialize(&mut &data[8..])?),
+        version if version == T::VERSION => err!(PandaBattleError::AlreadyMigrated),
+        _ => err!(PandaBattleError::UnsupportedAccountVersion),
+    }
+}
+
+/// Realloc `account` to the current size of `T` (topping up rent from `payer`) and
+/// write `value` to it
+fn write_current<'info, T: Versioned>(
+    account: &AccountInfo<'info>,
+    value: &T,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let space = 8 + T::INIT_SPACE;
+
+    let shortfall = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.to_account_info(),
+            to: account.clone(),
+        };
+        let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+        system_program::transfer(cpi_ctx, shortfall)?;
+    }
+
+    account.resize(space)?;
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+/// Whether `rules` match the balance the baseline program hardcoded, so players of a
+/// migrated round keep the rules they joined under
+fn is_baseline_rules(rules: &RoundRules) -> bool {
+    rules.max_turns == 9
+        && rules.starting_turns == 3
+        && rules.turns_per_hour == 3
+        && rules.level_xp_thresholds == [0, 5, 15, 30, 50, 75, 105, 140, 180, 225, 275]
+        && rules.xp_per_win == 3
+        && rules.max_attribute == 20
+        && rules.max_battle_turns == 10
+        && rules.crit_damage_multiplier == 5000
+        && rules.top_pool_pct == 80
+        && rules.leaderboard_size == 20
+}
@@ -1,7 +1,9 @@
 pub mod admin;
 pub mod player;
 pub mod crank;
+pub mod migrate;
 
 pub use admin::*;
 pub use player::*;
 pub use crank::*;
+pub use migrate::*;
@@ -1,52 +1,80 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{transfer, Token, TokenAccount, Transfer};
-use ephemeral_rollups_sdk::cpi::DelegateConfig;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use ephemeral_vrf_sdk::anchor::vrf;
 use ephemeral_vrf_sdk::instructions::{create_request_randomness_ix, RequestRandomnessParams};
 use ephemeral_vrf_sdk::types::SerializableAccountMeta;
 
 use crate::constants::*;
 use crate::errors::PandaBattleError;
+use crate::instructions::admin::RoundVault;
 use crate::state::*;
 
 /// Request to join the current round (Step 1: Request VRF)
-pub fn request_join_round(ctx: Context<RequestJoinRound>, client_seed: u8) -> Result<()> {
+/// Joining before the scheduled start is a registration at the base entry fee.
+/// Allowlisted rounds require a Merkle proof of the player's leaf, which may carry
+/// an entry-fee discount.
+pub fn request_join_round(
+    ctx: Context<RequestJoinRound>,
+    client_seed: u8,
+    allowlist_proof: Vec<[u8; 32]>,
+    discount_bps: u16,
+) -> Result<()> {
     {
+        ctx.accounts.global_config.require_not_paused(PAUSE_JOIN)?;
+
         let game_round = &mut ctx.accounts.game_round;
         let clock = Clock::get()?;
 
-        require!(game_round.is_active, PandaBattleError::RoundNotActive);
+        game_round.sync_status(clock.unix_timestamp);
+        require!(game_round.is_open(), PandaBattleError::RoundNotActive);
+        require!(!game_round.is_full(), PandaBattleError::RoundFull);
+        require!(
+            !game_round.is_underfilled(clock.unix_timestamp),
+            PandaBattleError::MinPlayersNotReached
+        );
+
+        let discount_bps = game_round.check_allowlist(
+            &ctx.accounts.player.key(),
+            discount_bps,
+            &allowlist_proof,
+        )?;
 
         // Calculate continuous entry fee: entry_fee * (1 + inc_pct/100 * hours_since_start).ceil()
-        let hours_since_start = (clock.unix_timestamp - game_round.start_time) / 3600;
+        let hours_since_start = game_round.hours_since_start(clock.unix_timestamp);
         let fee_multiplier = 100 + (game_round.entry_hourly_inc_pct as i64 * hours_since_start);
-        let entry_fee = ((game_round.entry_fee as u128 * fee_multiplier as u128 + 99) / 100) as u64; // Ceiling division
-
-        // Transfer entry fee to vault (SPL token)
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.player_token_account.to_account_info(),
-            to: ctx.accounts.vault.to_account_info(),
-            authority: ctx.accounts.player.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-        transfer(cpi_ctx, entry_fee)?;
-
-        // Update prize pool
-        game_round.prize_pool = game_round
-            .prize_pool
-            .checked_add(entry_fee)
-            .ok_or(PandaBattleError::Overflow)?;
+        let entry_fee =
+            (game_round.entry_fee as u128 * fee_multiplier as u128).div_ceil(100) as u64;
+        let entry_fee = (entry_fee as u128 * (10000 - discount_bps) as u128 / 10000) as u64;
+
+        // Transfer entry fee to vault; only what the vault receives is credited
+        let received = RoundVault {
+            currency: game_round.currency,
+            mint: ctx.accounts.mint.as_deref(),
+            vault: ctx.accounts.vault.as_deref_mut(),
+            sol_vault: ctx.accounts.sol_vault.as_deref(),
+            token_program: &ctx.accounts.token_program,
+            system_program: &ctx.accounts.system_program,
+        }
+        .deposit(
+            &ctx.accounts.player,
+            ctx.accounts.player_token_account.as_deref(),
+            entry_fee,
+        )?;
+
+        // Update prize pool (minus protocol rake)
+        game_round.credit_payment(received)?;
         game_round.player_count += 1;
+        game_round.open_player_states += 1;
 
         // Initialize player state with placeholder values (will be set by VRF callback)
         let player_state = &mut ctx.accounts.player_state;
+        player_state.version = PLAYER_STATE_VERSION;
         player_state.player = ctx.accounts.player.key();
         player_state.round = game_round.key();
-        player_state.entry_fee_paid = entry_fee;
+        player_state.entry_fee_paid = received;
         player_state.bump = ctx.bumps.player_state;
-        player_state.max_turns = 50; // Set max turns to 50
         player_state.last_pack_hour = -1; // Initialize to -1
-                                          // Attributes will be set by VRF callback
+                                          // Attributes and turns will be set by VRF callback
     }
 
     {
@@ -70,6 +98,11 @@ pub fn request_join_round(ctx: Context<RequestJoinRound>, client_seed: u8) -> Re
                     is_signer: false,
                     is_writable: false,
                 },
+                SerializableAccountMeta {
+                    pubkey: ctx.accounts.game_round.rules,
+                    is_signer: false,
+                    is_writable: false,
+                },
                 SerializableAccountMeta {
                     pubkey: ctx.accounts.buffer.key(),
                     is_signer: false,
@@ -117,6 +150,7 @@ pub fn callback_join_round(ctx: Context<CallbackJoinRound>, randomness: [u8; 32]
     {
         let player_state = &mut ctx.accounts.player_state;
         let game_round = &ctx.accounts.game_round;
+        let rules = &ctx.accounts.round_rules;
         let clock = Clock::get()?;
 
         // Generate attributes using VRF randomness (3 * u8 % 11 + 5 = range 5-15)
@@ -131,8 +165,10 @@ pub fn callback_join_round(ctx: Context<CallbackJoinRound>, randomness: [u8; 32]
         player_state.level = 0;
         player_state.xp = 0;
         player_state.points = 0;
-        player_state.turns = STARTING_TURNS;
-        player_state.last_turn_regen = clock.unix_timestamp;
+        player_state.max_turns = rules.max_turns;
+        player_state.turns = rules.starting_turns.min(rules.max_turns);
+        // Registered players start regenerating from the scheduled start
+        player_state.last_turn_regen = clock.unix_timestamp.max(game_round.start_time);
         player_state.rerolls_used = 0;
         player_state.packs_bought_hour = 0;
         player_state.last_battle = clock.unix_timestamp;
@@ -141,15 +177,16 @@ pub fn callback_join_round(ctx: Context<CallbackJoinRound>, randomness: [u8; 32]
         player_state.losses = 0;
         player_state.prize_share = 0;
         player_state.prize_claimed = false;
+        player_state.claim_expired = false;
         player_state.joined_at = clock.unix_timestamp;
 
-        // Early bird bonus: +2 turns if joined within first 6 hours
-        let hours_since_start = (clock.unix_timestamp - game_round.start_time) / 3600;
+        // Early bird bonus: +2 turns if joined within first 6 hours (or during registration)
+        let hours_since_start = game_round.hours_since_start(clock.unix_timestamp);
         if hours_since_start < 6 {
-            player_state.turns = player_state.turns.saturating_add(2);
-            if player_state.turns > player_state.max_turns {
-                player_state.turns = player_state.max_turns;
-            }
+            player_state.turns = player_state
+                .turns
+                .saturating_add(2)
+                .min(player_state.max_turns);
         }
 
         msg!(
@@ -187,10 +224,8 @@ pub fn callback_join_round(ctx: Context<CallbackJoinRound>, randomness: [u8; 32]
             player_key.as_ref(),
         ];
 
-        let config = DelegateConfig {
-            commit_frequency_ms: 30_000,
-            validator: Some(pubkey!("MAS1Dt9qreoRMQ14YQuhg8UTZMMzDdKhmkZMECCzk57")),
-        };
+        // Players follow their round onto the same ER validator
+        let config = game_round.delegation.delegate_config();
 
         player_state.exit(&crate::ID)?;
         ephemeral_rollups_sdk::cpi::delegate_account(del_accounts, seeds, config)?;
@@ -208,18 +243,26 @@ pub fn buy_attack_packs(ctx: Context<BuyAttackPacks>, num_packs: u8) -> Result<(
         num_packs > 0 && num_packs <= 5,
         PandaBattleError::TooManyTurns
     );
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_BUY_PACKS)?;
 
     let game_round = &mut ctx.accounts.game_round;
     let player_state = &mut ctx.accounts.player_state;
     let clock = Clock::get()?;
 
-    require!(game_round.is_active, PandaBattleError::RoundNotActive);
+    game_round.sync_status(clock.unix_timestamp);
+    require!(game_round.is_open(), PandaBattleError::RoundNotActive);
+    require!(
+        game_round.is_live(clock.unix_timestamp),
+        PandaBattleError::RoundNotStarted
+    );
 
     // Calculate turns to add (10 per pack)
     let turns_to_add = num_packs.saturating_mul(10);
     let new_turns = player_state.turns.saturating_add(turns_to_add);
 
-    // Check turn storage capacity (max 50)
+    // Check turn storage capacity (max_turns from round rules)
     require!(
         new_turns <= player_state.max_turns,
         PandaBattleError::TurnStorageFull
@@ -232,22 +275,29 @@ pub fn buy_attack_packs(ctx: Context<BuyAttackPacks>, num_packs: u8) -> Result<(
         (game_round.attack_pack_price as u128 * price_multiplier as u128 * num_packs as u128
             / 10000) as u64;
 
-    // Transfer payment to vault (SPL token)
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.player_token_account.to_account_info(),
-        to: ctx.accounts.vault.to_account_info(),
-        authority: ctx.accounts.player.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-    transfer(cpi_ctx, total_cost)?;
+    // Transfer payment to vault; only what the vault receives is credited
+    let received = RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .deposit(
+        &ctx.accounts.player,
+        ctx.accounts.player_token_account.as_ref(),
+        total_cost,
+    )?;
 
     // Update state
-    game_round.prize_pool = game_round
-        .prize_pool
-        .checked_add(total_cost)
-        .ok_or(PandaBattleError::Overflow)?;
+    game_round.credit_payment(received)?;
 
     player_state.turns = new_turns;
+    player_state.packs_spent = player_state
+        .packs_spent
+        .checked_add(received)
+        .ok_or(PandaBattleError::Overflow)?;
     player_state.packs_bought_hour = player_state.packs_bought_hour.saturating_add(num_packs);
     player_state.last_pack_hour = clock.unix_timestamp;
 
@@ -262,40 +312,46 @@ pub fn buy_attack_packs(ctx: Context<BuyAttackPacks>, num_packs: u8) -> Result<(
     Ok(())
 }
 
-/// Reroll attributes (costs $1 fixed, max 3 times)
+/// Reroll attributes (fee from the round's reroll schedule, max 3 times)
 pub fn reroll_attributes(ctx: Context<RerollAttributes>, client_seed: u8) -> Result<()> {
     {
+        ctx.accounts
+            .global_config
+            .require_not_paused(PAUSE_REROLL)?;
+
         let player_state = &mut ctx.accounts.player_state;
         let game_round = &ctx.accounts.game_round;
 
-        require!(game_round.is_active, PandaBattleError::RoundNotActive);
-        require!(
-            player_state.rerolls_used < 3,
-            PandaBattleError::MaxRerollsReached
-        );
-
-        // Fixed $1 fee (hardcoded - could be in config, but spec says fixed)
-        // Assuming token has same decimals as USDC (6 decimals), $1 = 1_000_000
-        let reroll_fee: u64 = 1_000_000;
-
-        // Transfer reroll fee to vault (SPL token)
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.player_token_account.to_account_info(),
-            to: ctx.accounts.vault.to_account_info(),
-            authority: ctx.accounts.player.to_account_info(),
-        };
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
-        transfer(cpi_ctx, reroll_fee)?;
+        require!(game_round.is_open(), PandaBattleError::RoundNotActive);
+        // Fee escalates with each reroll per the round's schedule
+        let reroll_fee = game_round
+            .reroll_fee(player_state.rerolls_used)
+            .ok_or(PandaBattleError::MaxRerollsReached)?;
+
+        // Transfer reroll fee to vault; only what the vault receives is credited
+        let received = RoundVault {
+            currency: game_round.currency,
+            mint: ctx.accounts.mint.as_ref(),
+            vault: ctx.accounts.vault.as_mut(),
+            sol_vault: ctx.accounts.sol_vault.as_ref(),
+            token_program: &ctx.accounts.token_program,
+            system_program: &ctx.accounts.system_program,
+        }
+        .deposit(
+            &ctx.accounts.player,
+            ctx.accounts.player_token_account.as_ref(),
+            reroll_fee,
+        )?;
 
-        // Update prize pool
-        let game_round = &mut ctx.accounts.game_round;
-        game_round.prize_pool = game_round
-            .prize_pool
-            .checked_add(reroll_fee)
-            .ok_or(PandaBattleError::Overflow)?;
+        // Update prize pool (minus protocol rake)
+        ctx.accounts.game_round.credit_payment(received)?;
 
         // Increment rerolls used
         player_state.rerolls_used = player_state.rerolls_used.saturating_add(1);
+        player_state.rerolls_spent = player_state
+            .rerolls_spent
+            .checked_add(received)
+            .ok_or(PandaBattleError::Overflow)?;
     }
 
     {
@@ -321,9 +377,10 @@ pub fn reroll_attributes(ctx: Context<RerollAttributes>, client_seed: u8) -> Res
             .invoke_signed_vrf(&ctx.accounts.player.to_account_info(), &ix)?;
 
         msg!(
-            "Player {} requested attribute reroll (reroll #{}/3)",
+            "Player {} requested attribute reroll (reroll #{}/{})",
             ctx.accounts.player.key(),
-            ctx.accounts.player_state.rerolls_used
+            ctx.accounts.player_state.rerolls_used,
+            MAX_REROLLS
         );
     }
 
@@ -361,11 +418,19 @@ pub fn callback_reroll_attributes(
 /// Initiate a battle against another player (Step 1: Request VRF for battle resolution)
 pub fn initiate_battle(ctx: Context<InitiateBattle>, client_seed: u8) -> Result<()> {
     {
+        ctx.accounts
+            .global_config
+            .require_not_paused(PAUSE_BATTLE)?;
+
         let game_round = &ctx.accounts.game_round;
         let attacker = &mut ctx.accounts.attacker_state;
         let defender = &ctx.accounts.defender_state;
 
-        require!(game_round.is_active, PandaBattleError::RoundNotActive);
+        require!(game_round.is_open(), PandaBattleError::RoundNotActive);
+        require!(
+            game_round.is_live(Clock::get()?.unix_timestamp),
+            PandaBattleError::RoundNotStarted
+        );
         require!(attacker.turns > 0, PandaBattleError::InsufficientTurns);
         require!(
             attacker.player != defender.player,
@@ -403,6 +468,11 @@ pub fn initiate_battle(ctx: Context<InitiateBattle>, client_seed: u8) -> Result<
                     is_signer: false,
                     is_writable: true,
                 },
+                SerializableAccountMeta {
+                    pubkey: ctx.accounts.game_round.rules,
+                    is_signer: false,
+                    is_writable: false,
+                },
             ]),
             ..Default::default()
         });
@@ -425,9 +495,12 @@ pub fn callback_resolve_battle(
     ctx: Context<CallbackResolveBattle>,
     randomness: [u8; 32],
 ) -> Result<()> {
+    // The turn was already spent in `initiate_battle`, so a pause in between must not
+    // strand it: pending battles always resolve
     let attacker = &mut ctx.accounts.attacker_state;
     let defender = &mut ctx.accounts.defender_state;
     let game_round = &mut ctx.accounts.game_round;
+    let rules = &ctx.accounts.round_rules;
     let clock = Clock::get()?;
 
     // Calculate HP for both fighters: 100 + (STR + INT) * 2
@@ -456,8 +529,11 @@ pub fn callback_resolve_battle(
     let mut random_index = 0;
     let mut attacker_wins = false;
 
-    // Battle simulation: max 10 turns
-    for turn in 0..MAX_BATTLE_TURNS {
+    // Crit multiplier in basis points (e.g. 15000 = 1.5x)
+    let crit_multiplier = 10000 + rules.crit_damage_multiplier as u32;
+
+    // Battle simulation: max turns from round rules
+    for turn in 0..rules.max_battle_turns {
         // Determine turn order: higher AGI goes first, tie uses VRF
         let attacker_goes_first = if attacker_agi == defender_agi {
             randomness[random_index % 32] == 0 // VRF tie-breaker
@@ -476,11 +552,7 @@ pub fn callback_resolve_battle(
 
             if is_attacker_turn {
                 // Attacker's turn
-                let agi_diff = if attacker_agi > defender_agi {
-                    attacker_agi - defender_agi
-                } else {
-                    0
-                };
+                let agi_diff = attacker_agi.saturating_sub(defender_agi);
                 let dodge_chance = (agi_diff as u16 * 10).min(100); // AGI_diff * 10%, cap 100%
                 let dodge_roll = (randomness[random_index % 32] as u16 * 100) / 255;
                 random_index += 1;
@@ -496,10 +568,10 @@ pub fn callback_resolve_battle(
                 random_index += 1;
                 let is_crit = crit_roll < crit_chance;
 
-                // Calculate damage: STR * (1 + 0.5 * crit) - INT/2
-                let base_damage = attacker_str as u16;
-                let crit_multiplier = if is_crit { 15000 } else { 10000 }; // 1.5x or 1.0x (basis points)
-                let damage_before_mitigation = (base_damage * crit_multiplier) / 10000;
+                // Calculate damage: STR * (1 + crit bonus) - INT/2
+                let base_damage = attacker_str as u32;
+                let multiplier = if is_crit { crit_multiplier } else { 10000 }; // basis points
+                let damage_before_mitigation = ((base_damage * multiplier) / 10000) as u16;
                 let mitigation = (defender_int as u16) / 2;
                 let final_damage = damage_before_mitigation.saturating_sub(mitigation);
 
@@ -518,11 +590,7 @@ pub fn callback_resolve_battle(
                 }
             } else {
                 // Defender's turn
-                let agi_diff = if defender_agi > attacker_agi {
-                    defender_agi - attacker_agi
-                } else {
-                    0
-                };
+                let agi_diff = defender_agi.saturating_sub(attacker_agi);
                 let dodge_chance = (agi_diff as u16 * 10).min(100);
                 let dodge_roll = (randomness[random_index % 32] as u16 * 100) / 255;
                 random_index += 1;
@@ -539,9 +607,9 @@ pub fn callback_resolve_battle(
                 let is_crit = crit_roll < crit_chance;
 
                 // Calculate damage
-                let base_damage = defender_str as u16;
-                let crit_multiplier = if is_crit { 15000 } else { 10000 };
-                let damage_before_mitigation = (base_damage * crit_multiplier) / 10000;
+                let base_damage = defender_str as u32;
+                let multiplier = if is_crit { crit_multiplier } else { 10000 };
+                let damage_before_mitigation = ((base_damage * multiplier) / 10000) as u16;
                 let mitigation = (attacker_int as u16) / 2;
                 let final_damage = damage_before_mitigation.saturating_sub(mitigation);
 
@@ -593,23 +661,31 @@ pub fn callback_resolve_battle(
     if attacker_wins {
         attacker.wins += 1;
         attacker.points += 1; // +1 point for leaderboard
-        attacker.xp += XP_PER_WIN; // +3 XP
+        attacker.xp = attacker.xp.saturating_add(rules.xp_per_win);
         defender.losses += 1;
 
-        msg!("Battle Result: {} WINS! (+1 point, +3 XP)", attacker.player);
+        msg!(
+            "Battle Result: {} WINS! (+1 point, +{} XP)",
+            attacker.player,
+            rules.xp_per_win
+        );
 
         // Check for level up
-        check_and_apply_levelup(attacker)?;
+        check_and_apply_levelup(attacker, rules)?;
     } else {
         attacker.losses += 1;
         defender.wins += 1;
         defender.points += 1;
-        defender.xp += XP_PER_WIN;
+        defender.xp = defender.xp.saturating_add(rules.xp_per_win);
 
-        msg!("Battle Result: {} WINS! (+1 point, +3 XP)", defender.player);
+        msg!(
+            "Battle Result: {} WINS! (+1 point, +{} XP)",
+            defender.player,
+            rules.xp_per_win
+        );
 
         // Check for level up
-        check_and_apply_levelup(defender)?;
+        check_and_apply_levelup(defender, rules)?;
     }
 
     game_round.total_battles += 1;
@@ -618,7 +694,7 @@ pub fn callback_resolve_battle(
 }
 
 /// Check if player should level up and apply stat boosts
-fn check_and_apply_levelup(player: &mut PlayerState) -> Result<()> {
+fn check_and_apply_levelup(player: &mut PlayerState, rules: &RoundRules) -> Result<()> {
     let current_level = player.level as usize;
 
     // Check if player can level up (max level 10)
@@ -627,7 +703,8 @@ fn check_and_apply_levelup(player: &mut PlayerState) -> Result<()> {
     }
 
     // Check if player has enough XP for next level
-    while player.level < MAX_LEVEL && player.xp >= LEVEL_XP_THRESHOLDS[(player.level + 1) as usize]
+    while player.level < MAX_LEVEL
+        && player.xp >= rules.level_xp_thresholds[(player.level + 1) as usize]
     {
         player.level += 1;
 
@@ -635,35 +712,29 @@ fn check_and_apply_levelup(player: &mut PlayerState) -> Result<()> {
         // This gives +0.5 effective boost per level (since we alternate)
         // Level 1: STR, Level 2: AGI, Level 3: INT, Level 4: STR, etc.
         match player.level % 3 {
-            1 => {
-                if player.str < MAX_ATTRIBUTE {
-                    player.str += 1;
-                    msg!(
-                        "Level Up! {} reached level {} (STR +1)",
-                        player.player,
-                        player.level
-                    );
-                }
+            1 if player.str < rules.max_attribute => {
+                player.str += 1;
+                msg!(
+                    "Level Up! {} reached level {} (STR +1)",
+                    player.player,
+                    player.level
+                );
             }
-            2 => {
-                if player.agi < MAX_ATTRIBUTE {
-                    player.agi += 1;
-                    msg!(
-                        "Level Up! {} reached level {} (AGI +1)",
-                        player.player,
-                        player.level
-                    );
-                }
+            2 if player.agi < rules.max_attribute => {
+                player.agi += 1;
+                msg!(
+                    "Level Up! {} reached level {} (AGI +1)",
+                    player.player,
+                    player.level
+                );
             }
-            0 => {
-                if player.int < MAX_ATTRIBUTE {
-                    player.int += 1;
-                    msg!(
-                        "Level Up! {} reached level {} (INT +1)",
-                        player.player,
-                        player.level
-                    );
-                }
+            0 if player.int < rules.max_attribute => {
+                player.int += 1;
+                msg!(
+                    "Level Up! {} reached level {} (INT +1)",
+                    player.player,
+                    player.level
+                );
             }
             _ => {}
         }
@@ -676,45 +747,56 @@ fn check_and_apply_levelup(player: &mut PlayerState) -> Result<()> {
 
 /// Claim prize after round ends
 pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+    ctx.accounts.global_config.require_not_paused(PAUSE_CLAIM)?;
+
     let game_round = &ctx.accounts.game_round;
     let player_state = &mut ctx.accounts.player_state;
-    let global_config = &ctx.accounts.global_config;
 
-    require!(!game_round.is_active, PandaBattleError::RoundNotEnded);
     require!(
-        game_round.payouts_processed,
-        PandaBattleError::NoRewardsAvailable
+        game_round.status != RoundStatus::Cancelled,
+        PandaBattleError::RoundCancelled
+    );
+    require!(
+        game_round.status == RoundStatus::Distributed,
+        PandaBattleError::PayoutsNotProcessed
     );
     require!(
         !player_state.prize_claimed,
         PandaBattleError::AlreadyClaimed
     );
+    require!(
+        !game_round.claims_expired(Clock::get()?.unix_timestamp),
+        PandaBattleError::ClaimWindowExpired
+    );
 
     // Use pre-calculated prize share
     let prize = player_state.prize_share;
     require!(prize > 0, PandaBattleError::NoRewardsAvailable);
 
     // Transfer prize from vault (SPL token with PDA signer - game_round is the authority)
-    let global_config_key = global_config.key();
     let round_number_bytes = game_round.round_number.to_le_bytes();
     let signer_seeds: &[&[&[u8]]] = &[&[
         GAME_ROUND_SEED,
-        global_config_key.as_ref(),
+        game_round.arena.as_ref(),
         round_number_bytes.as_ref(),
         &[game_round.bump],
     ]];
 
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.vault.to_account_info(),
-        to: ctx.accounts.player_token_account.to_account_info(),
-        authority: game_round.to_account_info(),
-    };
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
+    RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .pay_out(
+        &ctx.accounts.player.to_account_info(),
+        ctx.accounts.player_token_account.as_ref(),
+        &game_round.to_account_info(),
         signer_seeds,
-    );
-    transfer(cpi_ctx, prize)?;
+        prize,
+    )?;
 
     player_state.prize_claimed = true;
 
@@ -727,6 +809,212 @@ pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
     Ok(())
 }
 
+/// Claim a refund of everything paid into a cancelled round
+pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    ctx.accounts.global_config.require_not_paused(PAUSE_CLAIM)?;
+
+    let game_round = &mut ctx.accounts.game_round;
+    let player_state = &mut ctx.accounts.player_state;
+
+    require!(
+        game_round.status == RoundStatus::Cancelled,
+        PandaBattleError::RoundNotCancelled
+    );
+    require!(
+        !player_state.prize_claimed,
+        PandaBattleError::AlreadyClaimed
+    );
+    require!(
+        !game_round.claims_expired(Clock::get()?.unix_timestamp),
+        PandaBattleError::ClaimWindowExpired
+    );
+
+    // Pro rata, so a pool drained by hourly jackpots is short for everyone alike
+    let refund = player_state.refund_amount(game_round);
+    require!(refund > 0, PandaBattleError::NoRewardsAvailable);
+
+    game_round.prize_pool = game_round
+        .prize_pool
+        .checked_sub(refund)
+        .ok_or(PandaBattleError::Underflow)?;
+    player_state.prize_claimed = true;
+
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+        &[game_round.bump],
+    ]];
+
+    RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .pay_out(
+        &ctx.accounts.player.to_account_info(),
+        ctx.accounts.player_token_account.as_ref(),
+        &game_round.to_account_info(),
+        signer_seeds,
+        refund,
+    )?;
+
+    msg!(
+        "Player {} refunded {} tokens from cancelled round {}",
+        ctx.accounts.player.key(),
+        refund,
+        game_round.round_number
+    );
+
+    Ok(())
+}
+
+/// Deposit into a round's prize pool as a sponsor (anyone can call). No rake is taken;
+/// contributions are tracked per contributor and paid out by `distribute_prizes`, or
+/// refunded by `claim_sponsor_refund` if the round is cancelled. Runs on the base layer,
+/// where the round is usually delegated: the deposit is recorded in the round's
+/// `PendingDeposits` and folded into its pool when it is distributed or cancelled.
+pub fn sponsor_round(ctx: Context<SponsorRound>, round_number: u64, amount: u64) -> Result<()> {
+    ctx.accounts
+        .global_config
+        .require_not_paused(PAUSE_SPONSOR)?;
+    require!(amount > 0, PandaBattleError::InvalidConfig);
+
+    // The round's last committed state if it is delegated
+    let game_round =
+        GameRound::try_deserialize(&mut &ctx.accounts.game_round.try_borrow_data()?[..])?;
+    require!(
+        game_round.is_current(),
+        PandaBattleError::UnsupportedAccountVersion
+    );
+    // Deposits after the scheduled end could miss the fold at distribution
+    require!(
+        game_round.is_open() && Clock::get()?.unix_timestamp < game_round.end_time,
+        PandaBattleError::RoundNotActive
+    );
+    if let Some(mint) = ctx.accounts.mint.as_ref() {
+        require_keys_eq!(mint.key(), game_round.mint, PandaBattleError::InvalidMint);
+    }
+    if let Some(sponsor_token_account) = ctx.accounts.sponsor_token_account.as_ref() {
+        require_keys_eq!(
+            sponsor_token_account.mint,
+            game_round.mint,
+            PandaBattleError::InvalidMint
+        );
+    }
+
+    let received = RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .deposit(
+        &ctx.accounts.sponsor,
+        ctx.accounts.sponsor_token_account.as_ref(),
+        amount,
+    )?;
+
+    let round_key = ctx.accounts.game_round.key();
+    let pending = &mut ctx.accounts.pending_deposits;
+    if pending.round == Pubkey::default() {
+        pending.round = round_key;
+        pending.rent_payer = ctx.accounts.sponsor.key();
+        pending.bump = ctx.bumps.pending_deposits;
+    }
+    pending.sponsored = pending
+        .sponsored
+        .checked_add(received)
+        .ok_or(PandaBattleError::Overflow)?;
+
+    let sponsor = &mut ctx.accounts.sponsor_record;
+    // A fresh record is counted so the round can't be closed before it
+    if sponsor.round == Pubkey::default() {
+        pending.sponsors = pending
+            .sponsors
+            .checked_add(1)
+            .ok_or(PandaBattleError::Overflow)?;
+    }
+    sponsor.round = round_key;
+    sponsor.sponsor = ctx.accounts.sponsor.key();
+    sponsor.amount = sponsor
+        .amount
+        .checked_add(received)
+        .ok_or(PandaBattleError::Overflow)?;
+    sponsor.bump = ctx.bumps.sponsor_record;
+
+    msg!(
+        "{} sponsored round {} with {}. Pending sponsorship: {}",
+        sponsor.sponsor,
+        round_number,
+        received,
+        pending.sponsored
+    );
+
+    Ok(())
+}
+
+/// Reclaim a sponsorship of a cancelled round: the sponsor's pro-rata share of the
+/// refund pool. Closes the sponsor record, returning its rent.
+pub fn claim_sponsor_refund(ctx: Context<ClaimSponsorRefund>) -> Result<()> {
+    ctx.accounts.global_config.require_not_paused(PAUSE_CLAIM)?;
+
+    let game_round = &mut ctx.accounts.game_round;
+    require!(
+        !game_round.unclaimed_swept && !game_round.claims_expired(Clock::get()?.unix_timestamp),
+        PandaBattleError::ClaimWindowExpired
+    );
+
+    let refund = game_round.refund_share(ctx.accounts.sponsor_record.amount);
+    require!(refund > 0, PandaBattleError::NoRewardsAvailable);
+
+    game_round.prize_pool = game_round
+        .prize_pool
+        .checked_sub(refund)
+        .ok_or(PandaBattleError::Underflow)?;
+    // Sponsorships made after the round's deposits were folded in aren't counted
+    game_round.open_sponsors = game_round.open_sponsors.saturating_sub(1);
+
+    let round_number_bytes = game_round.round_number.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        GAME_ROUND_SEED,
+        game_round.arena.as_ref(),
+        round_number_bytes.as_ref(),
+        &[game_round.bump],
+    ]];
+
+    RoundVault {
+        currency: game_round.currency,
+        mint: ctx.accounts.mint.as_ref(),
+        vault: ctx.accounts.vault.as_mut(),
+        sol_vault: ctx.accounts.sol_vault.as_ref(),
+        token_program: &ctx.accounts.token_program,
+        system_program: &ctx.accounts.system_program,
+    }
+    .pay_out(
+        &ctx.accounts.sponsor.to_account_info(),
+        ctx.accounts.sponsor_token_account.as_ref(),
+        &game_round.to_account_info(),
+        signer_seeds,
+        refund,
+    )?;
+
+    msg!(
+        "Sponsor {} refunded {} from cancelled round {}",
+        ctx.accounts.sponsor.key(),
+        refund,
+        game_round.round_number
+    );
+
+    Ok(())
+}
+
 // ============== CONTEXTS ==============
 
 #[vrf]
@@ -737,7 +1025,8 @@ pub struct RequestJoinRound<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Box<Account<'info, GlobalConfig>>,
 
@@ -745,11 +1034,12 @@ pub struct RequestJoinRound<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
         bump = game_round.bump,
-        constraint = game_round.is_active @ PandaBattleError::RoundNotActive
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.is_open() @ PandaBattleError::RoundNotActive
     )]
     pub game_round: Box<Account<'info, GameRound>>,
 
@@ -766,21 +1056,33 @@ pub struct RequestJoinRound<'info> {
     )]
     pub player_state: Box<Account<'info, PlayerState>>,
 
-    /// Player's token account
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Player's token account (token rounds only)
     #[account(
         mut,
         constraint = player_token_account.owner == player.key() @ PandaBattleError::Unauthorized,
-        constraint = player_token_account.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = player_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
     )]
-    pub player_token_account: Box<Account<'info, TokenAccount>>,
+    pub player_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// Vault token account for this round (ATA owned by game_round)
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
     #[account(
         mut,
         constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
-        constraint = vault.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
     )]
-    pub vault: Box<Account<'info, TokenAccount>>,
+    pub vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Box<Account<'info, SolVault>>>,
 
     /// CHECK: The oracle queue for VRF
     #[account(mut, address = ephemeral_vrf_sdk::consts::DEFAULT_QUEUE)]
@@ -822,7 +1124,7 @@ pub struct RequestJoinRound<'info> {
     pub delegation_program: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -831,11 +1133,18 @@ pub struct CallbackJoinRound<'info> {
     #[account(mut, address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY)]
     pub vrf_program_identity: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
     pub player_state: Account<'info, PlayerState>,
 
+    #[account(constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion)]
     pub game_round: Account<'info, GameRound>,
 
+    #[account(address = game_round.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
+
     /// CHECK: The buffer account
     #[account(
         mut,
@@ -881,7 +1190,8 @@ pub struct BuyAttackPacks<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -889,10 +1199,11 @@ pub struct BuyAttackPacks<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub game_round: Account<'info, GameRound>,
 
@@ -904,28 +1215,41 @@ pub struct BuyAttackPacks<'info> {
             player.key().as_ref()
         ],
         bump = player_state.bump,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion,
         constraint = player_state.player == player.key() @ PandaBattleError::NotJoined
     )]
     pub player_state: Account<'info, PlayerState>,
 
-    /// Player's token account
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Player's token account (token rounds only)
     #[account(
         mut,
         constraint = player_token_account.owner == player.key() @ PandaBattleError::Unauthorized,
-        constraint = player_token_account.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = player_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Vault token account for this round (ATA owned by game_round)
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
     #[account(
         mut,
         constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
-        constraint = vault.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub sol_vault: Option<Account<'info, SolVault>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[vrf]
@@ -936,7 +1260,8 @@ pub struct RerollAttributes<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -944,11 +1269,12 @@ pub struct RerollAttributes<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
         bump = game_round.bump,
-        constraint = game_round.is_active @ PandaBattleError::RoundNotActive
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.is_open() @ PandaBattleError::RoundNotActive
     )]
     pub game_round: Account<'info, GameRound>,
 
@@ -960,32 +1286,45 @@ pub struct RerollAttributes<'info> {
             player.key().as_ref()
         ],
         bump = player_state.bump,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion,
         constraint = player_state.player == player.key() @ PandaBattleError::NotJoined
     )]
     pub player_state: Account<'info, PlayerState>,
 
-    /// Player's token account
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Player's token account (token rounds only)
     #[account(
         mut,
         constraint = player_token_account.owner == player.key() @ PandaBattleError::Unauthorized,
-        constraint = player_token_account.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = player_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Vault token account for this round (ATA owned by game_round)
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
     #[account(
         mut,
         constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
-        constraint = vault.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub sol_vault: Option<Account<'info, SolVault>>,
 
     /// CHECK: The oracle queue for VRF
     #[account(mut, address = ephemeral_vrf_sdk::consts::DEFAULT_QUEUE)]
     pub oracle_queue: AccountInfo<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -994,7 +1333,10 @@ pub struct CallbackRerollAttributes<'info> {
     #[account(address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY)]
     pub vrf_program_identity: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
     pub player_state: Account<'info, PlayerState>,
 }
 
@@ -1006,7 +1348,8 @@ pub struct InitiateBattle<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -1014,10 +1357,11 @@ pub struct InitiateBattle<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
-        bump = game_round.bump
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub game_round: Account<'info, GameRound>,
 
@@ -1029,6 +1373,7 @@ pub struct InitiateBattle<'info> {
             player.key().as_ref()
         ],
         bump = attacker_state.bump,
+        constraint = attacker_state.is_current() @ PandaBattleError::UnsupportedAccountVersion,
         constraint = attacker_state.player == player.key() @ PandaBattleError::NotJoined
     )]
     pub attacker_state: Account<'info, PlayerState>,
@@ -1040,7 +1385,8 @@ pub struct InitiateBattle<'info> {
             game_round.key().as_ref(),
             defender_state.player.as_ref()
         ],
-        bump = defender_state.bump
+        bump = defender_state.bump,
+        constraint = defender_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub defender_state: Account<'info, PlayerState>,
 
@@ -1057,14 +1403,26 @@ pub struct CallbackResolveBattle<'info> {
     #[account(address = ephemeral_vrf_sdk::consts::VRF_PROGRAM_IDENTITY)]
     pub vrf_program_identity: Signer<'info>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = attacker_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
     pub attacker_state: Account<'info, PlayerState>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = defender_state.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
     pub defender_state: Account<'info, PlayerState>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
     pub game_round: Account<'info, GameRound>,
+
+    #[account(address = game_round.rules @ PandaBattleError::InvalidRules)]
+    pub round_rules: Account<'info, RoundRules>,
 }
 
 #[derive(Accounts)]
@@ -1074,7 +1432,8 @@ pub struct ClaimPrize<'info> {
 
     #[account(
         seeds = [GLOBAL_CONFIG_SEED],
-        bump = global_config.bump
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
     )]
     pub global_config: Account<'info, GlobalConfig>,
 
@@ -1082,12 +1441,13 @@ pub struct ClaimPrize<'info> {
         mut,
         seeds = [
             GAME_ROUND_SEED,
-            global_config.key().as_ref(),
+            game_round.arena.as_ref(),
             game_round.round_number.to_le_bytes().as_ref()
         ],
         bump = game_round.bump,
-        constraint = !game_round.is_active @ PandaBattleError::RoundNotEnded,
-        constraint = game_round.payouts_processed @ PandaBattleError::NoRewardsAvailable
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status == RoundStatus::Distributed
+            @ PandaBattleError::PayoutsNotProcessed
     )]
     pub game_round: Account<'info, GameRound>,
 
@@ -1099,26 +1459,267 @@ pub struct ClaimPrize<'info> {
             player.key().as_ref()
         ],
         bump = player_state.bump,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion,
         constraint = player_state.player == player.key() @ PandaBattleError::NotJoined
     )]
     pub player_state: Account<'info, PlayerState>,
 
-    /// Player's token account
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Player's token account (token rounds only)
     #[account(
         mut,
         constraint = player_token_account.owner == player.key() @ PandaBattleError::Unauthorized,
-        constraint = player_token_account.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = player_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
     )]
-    pub player_token_account: Account<'info, TokenAccount>,
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// Vault token account for this round (ATA owned by game_round)
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
     #[account(
         mut,
         constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
-        constraint = vault.mint == global_config.token_mint @ PandaBattleError::InvalidMint
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status == RoundStatus::Cancelled
+            @ PandaBattleError::RoundNotCancelled
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    #[account(
+        mut,
+        seeds = [
+            PLAYER_STATE_SEED,
+            game_round.key().as_ref(),
+            player.key().as_ref()
+        ],
+        bump = player_state.bump,
+        constraint = player_state.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = player_state.player == player.key() @ PandaBattleError::NotJoined
+    )]
+    pub player_state: Account<'info, PlayerState>,
+
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Player's token account (token rounds only)
+    #[account(
+        mut,
+        constraint = player_token_account.owner == player.key() @ PandaBattleError::Unauthorized,
+        constraint = player_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub player_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Vault token account for this round (ATA owned by game_round; token rounds only)
+    #[account(
+        mut,
+        constraint = vault.owner == game_round.key() @ PandaBattleError::Unauthorized,
+        constraint = vault.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(round_number: u64)]
+pub struct SponsorRound<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Only used to derive the round's address
+    pub arena: UncheckedAccount<'info>,
+
+    /// CHECK: Read in the handler. It is usually delegated, so it is owned by this program
+    /// or the delegation program.
+    #[account(
+        seeds = [
+            GAME_ROUND_SEED,
+            arena.key().as_ref(),
+            round_number.to_le_bytes().as_ref()
+        ],
+        bump,
+        constraint = *game_round.owner == crate::ID
+            || *game_round.owner == ::ephemeral_rollups_sdk::id()
+            @ PandaBattleError::InvalidRound
+    )]
+    pub game_round: UncheckedAccount<'info>,
+
+    /// Sponsorships of the round, folded into its pool when it settles
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = 8 + PendingDeposits::INIT_SPACE,
+        seeds = [PENDING_DEPOSITS_SEED, game_round.key().as_ref()],
+        bump
+    )]
+    pub pending_deposits: Account<'info, PendingDeposits>,
+
+    /// Running total of this contributor's sponsorship of the round
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = 8 + Sponsor::INIT_SPACE,
+        seeds = [
+            SPONSOR_SEED,
+            game_round.key().as_ref(),
+            sponsor.key().as_ref()
+        ],
+        bump
+    )]
+    pub sponsor_record: Account<'info, Sponsor>,
+
+    /// Token mint of this round (token rounds only; checked against the round in the handler)
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Sponsor's token account (token rounds only)
+    #[account(
+        mut,
+        constraint = sponsor_token_account.owner == sponsor.key() @ PandaBattleError::Unauthorized
+    )]
+    pub sponsor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The round's vault ATA (token rounds only)
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
+    )]
+    pub sol_vault: Option<Account<'info, SolVault>>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimSponsorRefund<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+
+    #[account(
+        seeds = [GLOBAL_CONFIG_SEED],
+        bump = global_config.bump,
+        constraint = global_config.is_current() @ PandaBattleError::UnsupportedAccountVersion
+    )]
+    pub global_config: Account<'info, GlobalConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            GAME_ROUND_SEED,
+            game_round.arena.as_ref(),
+            game_round.round_number.to_le_bytes().as_ref()
+        ],
+        bump = game_round.bump,
+        constraint = game_round.is_current() @ PandaBattleError::UnsupportedAccountVersion,
+        constraint = game_round.status == RoundStatus::Cancelled
+            @ PandaBattleError::RoundNotCancelled
+    )]
+    pub game_round: Account<'info, GameRound>,
+
+    #[account(
+        mut,
+        seeds = [
+            SPONSOR_SEED,
+            game_round.key().as_ref(),
+            sponsor.key().as_ref()
+        ],
+        bump = sponsor_record.bump,
+        close = sponsor
+    )]
+    pub sponsor_record: Account<'info, Sponsor>,
+
+    /// Token mint of this round (token rounds only)
+    #[account(address = game_round.mint @ PandaBattleError::InvalidMint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// Sponsor's token account (token rounds only)
+    #[account(
+        mut,
+        constraint = sponsor_token_account.owner == sponsor.key() @ PandaBattleError::Unauthorized,
+        constraint = sponsor_token_account.mint == game_round.mint @ PandaBattleError::InvalidMint
+    )]
+    pub sponsor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The round's vault ATA (token rounds only)
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = game_round,
+        associated_token::token_program = token_program,
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Lamport vault for this round (native SOL rounds only)
+    #[account(
+        mut,
+        seeds = [SOL_VAULT_SEED, game_round.key().as_ref()],
+        bump = sol_vault.bump
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub sol_vault: Option<Account<'info, SolVault>>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
@@ -7,110 +7,304 @@ pub mod instructions;
 pub mod state;
 
 use instructions::*;
+use state::{Currency, DelegationSettings, RoundParams, RoundRulesParams};
 
 declare_id!("H7UJumnqZJjHNcmfTjcnM3vyz23g4DNNZbh5upWF6ECP");
 
-#[ephemeral]
-#[program]
-pub mod panda_battle {
+// `#[program]` expands to the deprecated `AccountInfo::realloc` in the IDL instruction
+// handlers it emits next to the program module, so the program lives in a module of its
+// own to keep the allow off the rest of the crate
+#[allow(deprecated)]
+mod program_entry {
     use super::*;
 
-    // ============== ADMIN INSTRUCTIONS ==============
+    #[ephemeral]
+    #[program]
+    pub mod panda_battle {
+        use super::*;
 
-    pub fn initialize_game(ctx: Context<InitializeGame>, token_mint: Pubkey) -> Result<()> {
-        instructions::admin::initialize_game(ctx, token_mint)
-    }
+        // ============== ADMIN INSTRUCTIONS ==============
 
-    pub fn create_round(
-        ctx: Context<CreateRound>,
-        entry_fee: u64,
-        attack_pack_price: u64,
-        duration_secs: i64,
-        entry_hourly_inc_pct: u8,
-    ) -> Result<()> {
-        instructions::admin::create_round(
-            ctx,
-            entry_fee,
-            attack_pack_price,
-            duration_secs,
-            entry_hourly_inc_pct,
-        )
-    }
+        pub fn initialize_game(ctx: Context<InitializeGame>) -> Result<()> {
+            instructions::admin::initialize_game(ctx)
+        }
 
-    pub fn delegate_round(ctx: Context<DelegateRound>) -> Result<()> {
-        instructions::admin::delegate_round(ctx)
-    }
+        pub fn create_arena(
+            ctx: Context<CreateArena>,
+            arena_id: u32,
+            name: [u8; 32],
+        ) -> Result<()> {
+            instructions::admin::create_arena(ctx, arena_id, name)
+        }
 
-    pub fn end_round(ctx: Context<EndRound>) -> Result<()> {
-        instructions::admin::end_round(ctx)
-    }
+        pub fn update_arena(
+            ctx: Context<UpdateArena>,
+            protocol_fee_bps: Option<u16>,
+            delegation: Option<DelegationSettings>,
+        ) -> Result<()> {
+            instructions::admin::update_arena(ctx, protocol_fee_bps, delegation)
+        }
 
-    pub fn update_config(ctx: Context<UpdateConfig>, token_mint: Option<Pubkey>) -> Result<()> {
-        instructions::admin::update_config(ctx, token_mint)
-    }
+        pub fn create_round_rules(
+            ctx: Context<CreateRoundRules>,
+            rules_id: u32,
+            params: RoundRulesParams,
+        ) -> Result<()> {
+            instructions::admin::create_round_rules(ctx, rules_id, params)
+        }
 
-    // ============== PLAYER INSTRUCTIONS ==============
+        pub fn create_round(
+            ctx: Context<CreateRound>,
+            params: RoundParams,
+            start_time: Option<i64>,
+            delegation: Option<DelegationSettings>,
+            currency: Currency,
+        ) -> Result<()> {
+            instructions::admin::create_round(ctx, params, start_time, delegation, currency)
+        }
 
-    pub fn request_join_round(ctx: Context<RequestJoinRound>, client_seed: u8) -> Result<()> {
-        instructions::player::request_join_round(ctx, client_seed)
-    }
+        pub fn create_round_template(
+            ctx: Context<CreateRoundTemplate>,
+            template_id: u32,
+            name: [u8; 32],
+            params: RoundParams,
+            currency: Currency,
+        ) -> Result<()> {
+            instructions::admin::create_round_template(ctx, template_id, name, params, currency)
+        }
 
-    pub fn callback_join_round(
-        ctx: Context<CallbackJoinRound>,
-        randomness: [u8; 32],
-    ) -> Result<()> {
-        instructions::player::callback_join_round(ctx, randomness)
-    }
+        pub fn update_round_template(
+            ctx: Context<UpdateRoundTemplate>,
+            name: Option<[u8; 32]>,
+            params: RoundParams,
+            currency: Currency,
+        ) -> Result<()> {
+            instructions::admin::update_round_template(ctx, name, params, currency)
+        }
 
-    pub fn buy_attack_packs(ctx: Context<BuyAttackPacks>, num_packs: u8) -> Result<()> {
-        instructions::player::buy_attack_packs(ctx, num_packs)
-    }
+        pub fn create_round_from_template(
+            ctx: Context<CreateRoundFromTemplate>,
+            start_time: Option<i64>,
+        ) -> Result<()> {
+            instructions::admin::create_round_from_template(ctx, start_time)
+        }
 
-    pub fn reroll_attributes(ctx: Context<RerollAttributes>, client_seed: u8) -> Result<()> {
-        instructions::player::reroll_attributes(ctx, client_seed)
-    }
+        pub fn delegate_round(ctx: Context<DelegateRound>) -> Result<()> {
+            instructions::admin::delegate_round(ctx)
+        }
 
-    pub fn callback_reroll_attributes(
-        ctx: Context<CallbackRerollAttributes>,
-        randomness: [u8; 32],
-    ) -> Result<()> {
-        instructions::player::callback_reroll_attributes(ctx, randomness)
-    }
+        pub fn end_round(ctx: Context<EndRound>) -> Result<()> {
+            instructions::admin::end_round(ctx)
+        }
 
-    pub fn initiate_battle(ctx: Context<InitiateBattle>, client_seed: u8) -> Result<()> {
-        instructions::player::initiate_battle(ctx, client_seed)
-    }
+        pub fn update_round_params(
+            ctx: Context<UpdateRoundParams>,
+            params: RoundParams,
+            start_time: Option<i64>,
+        ) -> Result<()> {
+            instructions::admin::update_round_params(ctx, params, start_time)
+        }
 
-    pub fn callback_resolve_battle(
-        ctx: Context<CallbackResolveBattle>,
-        randomness: [u8; 32],
-    ) -> Result<()> {
-        instructions::player::callback_resolve_battle(ctx, randomness)
-    }
+        pub fn set_round_allowlist(
+            ctx: Context<SetRoundAllowlist>,
+            allowlist_root: Option<[u8; 32]>,
+        ) -> Result<()> {
+            instructions::admin::set_round_allowlist(ctx, allowlist_root)
+        }
 
-    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
-        instructions::player::claim_prize(ctx)
-    }
+        pub fn cancel_round(ctx: Context<CancelRound>) -> Result<()> {
+            instructions::admin::cancel_round(ctx)
+        }
 
-    // ============== CRANK INSTRUCTIONS ==============
+        pub fn update_config(
+            ctx: Context<UpdateConfig>,
+            protocol_fee_bps: Option<u16>,
+        ) -> Result<()> {
+            instructions::admin::update_config(ctx, protocol_fee_bps)
+        }
 
-    pub fn regenerate_turns(ctx: Context<RegenerateTurns>) -> Result<()> {
-        instructions::crank::regenerate_turns(ctx)
-    }
+        pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Option<Pubkey>) -> Result<()> {
+            instructions::admin::propose_admin(ctx, new_admin)
+        }
 
-    pub fn reset_packs_if_new_hour(ctx: Context<ResetPacksIfNewHour>) -> Result<()> {
-        instructions::crank::reset_packs_if_new_hour(ctx)
-    }
+        pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+            instructions::admin::accept_admin(ctx)
+        }
 
-    pub fn reveal_leaderboard(ctx: Context<RevealLeaderboard>) -> Result<()> {
-        instructions::crank::reveal_leaderboard(ctx)
-    }
+        pub fn set_roles(
+            ctx: Context<SetRoles>,
+            operator: Option<Pubkey>,
+            treasurer: Option<Pubkey>,
+        ) -> Result<()> {
+            instructions::admin::set_roles(ctx, operator, treasurer)
+        }
 
-    pub fn hourly_jackpot(ctx: Context<HourlyJackpot>, client_seed: u8) -> Result<()> {
-        instructions::crank::hourly_jackpot(ctx, client_seed)
-    }
+        pub fn set_pause(ctx: Context<SetPause>, paused_actions: u8) -> Result<()> {
+            instructions::admin::set_pause(ctx, paused_actions)
+        }
+
+        pub fn set_delegation_settings(
+            ctx: Context<SetDelegationSettings>,
+            delegation: DelegationSettings,
+        ) -> Result<()> {
+            instructions::admin::set_delegation_settings(ctx, delegation)
+        }
+
+        pub fn set_rollover(ctx: Context<SetRollover>, requires_distribution: bool) -> Result<()> {
+            instructions::admin::set_rollover(ctx, requires_distribution)
+        }
+
+        pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+            instructions::admin::withdraw_treasury(ctx, amount)
+        }
+
+        // ============== PLAYER INSTRUCTIONS ==============
+
+        pub fn request_join_round(
+            ctx: Context<RequestJoinRound>,
+            client_seed: u8,
+            allowlist_proof: Vec<[u8; 32]>,
+            discount_bps: u16,
+        ) -> Result<()> {
+            instructions::player::request_join_round(
+                ctx,
+                client_seed,
+                allowlist_proof,
+                discount_bps,
+            )
+        }
+
+        pub fn callback_join_round(
+            ctx: Context<CallbackJoinRound>,
+            randomness: [u8; 32],
+        ) -> Result<()> {
+            instructions::player::callback_join_round(ctx, randomness)
+        }
+
+        pub fn buy_attack_packs(ctx: Context<BuyAttackPacks>, num_packs: u8) -> Result<()> {
+            instructions::player::buy_attack_packs(ctx, num_packs)
+        }
+
+        pub fn reroll_attributes(ctx: Context<RerollAttributes>, client_seed: u8) -> Result<()> {
+            instructions::player::reroll_attributes(ctx, client_seed)
+        }
 
-    pub fn distribute_prizes(ctx: Context<DistributePrizes>) -> Result<()> {
-        instructions::crank::distribute_prizes(ctx)
+        pub fn callback_reroll_attributes(
+            ctx: Context<CallbackRerollAttributes>,
+            randomness: [u8; 32],
+        ) -> Result<()> {
+            instructions::player::callback_reroll_attributes(ctx, randomness)
+        }
+
+        pub fn initiate_battle(ctx: Context<InitiateBattle>, client_seed: u8) -> Result<()> {
+            instructions::player::initiate_battle(ctx, client_seed)
+        }
+
+        pub fn callback_resolve_battle(
+            ctx: Context<CallbackResolveBattle>,
+            randomness: [u8; 32],
+        ) -> Result<()> {
+            instructions::player::callback_resolve_battle(ctx, randomness)
+        }
+
+        pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+            instructions::player::claim_prize(ctx)
+        }
+
+        pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+            instructions::player::claim_refund(ctx)
+        }
+
+        pub fn sponsor_round(
+            ctx: Context<SponsorRound>,
+            round_number: u64,
+            amount: u64,
+        ) -> Result<()> {
+            instructions::player::sponsor_round(ctx, round_number, amount)
+        }
+
+        pub fn claim_sponsor_refund(ctx: Context<ClaimSponsorRefund>) -> Result<()> {
+            instructions::player::claim_sponsor_refund(ctx)
+        }
+
+        // ============== CRANK INSTRUCTIONS ==============
+
+        pub fn regenerate_turns(ctx: Context<RegenerateTurns>) -> Result<()> {
+            instructions::crank::regenerate_turns(ctx)
+        }
+
+        pub fn reset_packs_if_new_hour(ctx: Context<ResetPacksIfNewHour>) -> Result<()> {
+            instructions::crank::reset_packs_if_new_hour(ctx)
+        }
+
+        pub fn reveal_leaderboard(ctx: Context<RevealLeaderboard>) -> Result<()> {
+            instructions::crank::reveal_leaderboard(ctx)
+        }
+
+        pub fn hourly_jackpot(ctx: Context<HourlyJackpot>, client_seed: u8) -> Result<()> {
+            instructions::crank::hourly_jackpot(ctx, client_seed)
+        }
+
+        pub fn distribute_prizes(ctx: Context<DistributePrizes>) -> Result<()> {
+            instructions::crank::distribute_prizes(ctx)
+        }
+
+        pub fn cancel_underfilled_round(ctx: Context<CancelUnderfilledRound>) -> Result<()> {
+            instructions::crank::cancel_underfilled_round(ctx)
+        }
+
+        pub fn settle_round<'info>(
+            ctx: Context<'_, '_, '_, 'info, SettleRound<'info>>,
+            undelegate_round: bool,
+        ) -> Result<()> {
+            instructions::crank::settle_round(ctx, undelegate_round)
+        }
+
+        pub fn commit_round_state<'info>(
+            ctx: Context<'_, '_, '_, 'info, CommitRoundState<'info>>,
+        ) -> Result<()> {
+            instructions::crank::commit_round_state(ctx)
+        }
+
+        pub fn start_next_round(ctx: Context<StartNextRound>) -> Result<()> {
+            instructions::crank::start_next_round(ctx)
+        }
+
+        pub fn sweep_unclaimed(ctx: Context<SweepUnclaimed>) -> Result<()> {
+            instructions::crank::sweep_unclaimed(ctx)
+        }
+
+        pub fn close_player_state(ctx: Context<ClosePlayerState>) -> Result<()> {
+            instructions::crank::close_player_state(ctx)
+        }
+
+        pub fn close_sponsor(ctx: Context<CloseSponsor>) -> Result<()> {
+            instructions::crank::close_sponsor(ctx)
+        }
+
+        pub fn close_round(ctx: Context<CloseRound>) -> Result<()> {
+            instructions::crank::close_round(ctx)
+        }
+
+        // ============== MIGRATION INSTRUCTIONS ==============
+
+        pub fn migrate_global_config(ctx: Context<MigrateGlobalConfig>) -> Result<()> {
+            instructions::migrate::migrate_global_config(ctx)
+        }
+
+        pub fn migrate_game_round(ctx: Context<MigrateGameRound>, round_number: u64) -> Result<()> {
+            instructions::migrate::migrate_game_round(ctx, round_number)
+        }
+
+        pub fn migrate_player_state(ctx: Context<MigratePlayerState>) -> Result<()> {
+            instructions::migrate::migrate_player_state(ctx)
+        }
     }
 }
+
+pub use program_entry::*;
+// `#[program]` looks up the client account modules of `#[ephemeral]`'s injected
+// instruction at the crate root
+pub(crate) use program_entry::panda_battle::__client_accounts_initialize_after_undelegation;
+#[cfg(feature = "cpi")]
+pub(crate) use program_entry::panda_battle::__cpi_client_accounts_initialize_after_undelegation;
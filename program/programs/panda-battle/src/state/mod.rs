@@ -1,20 +1,99 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use ephemeral_rollups_sdk::cpi::DelegateConfig;
+
+use crate::constants::*;
+use crate::errors::PandaBattleError;
+
+/// Accounts whose leading `version` byte lets old layouts be upgraded in place by the
+/// `migrate_*` instructions. The baseline layouts from before versioning count as version 0.
+pub trait Versioned: AccountSerialize + AccountDeserialize + Discriminator + Space {
+    /// Current layout version
+    const VERSION: u8;
+
+    /// Size (without discriminator) of the unversioned baseline layout (version 0)
+    const V0_SPACE: usize;
+
+    /// Layout version of a deserialized account
+    fn version(&self) -> u8;
+
+    /// Whether the account is at the current layout. Older accounts may still deserialize
+    /// into the wrong fields, so every instruction but `migrate_*` rejects them.
+    fn is_current(&self) -> bool {
+        self.version() == Self::VERSION
+    }
+
+    /// Layout version of raw account data (discriminator included). Only known sizes are
+    /// accepted: the baseline layout, or the current one, which carries its version byte.
+    fn version_of(data: &[u8]) -> Result<u8> {
+        if data.len() == 8 + Self::V0_SPACE {
+            return Ok(0);
+        }
+        require!(
+            data.len() == 8 + Self::INIT_SPACE,
+            PandaBattleError::UnsupportedAccountVersion
+        );
+        Ok(data[8])
+    }
+}
+
+impl Versioned for GlobalConfig {
+    const VERSION: u8 = GLOBAL_CONFIG_VERSION;
+    const V0_SPACE: usize = GLOBAL_CONFIG_V0_SPACE;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+impl Versioned for GameRound {
+    const VERSION: u8 = GAME_ROUND_VERSION;
+    const V0_SPACE: usize = GAME_ROUND_V0_SPACE;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+impl Versioned for PlayerState {
+    const VERSION: u8 = PLAYER_STATE_VERSION;
+    const V0_SPACE: usize = PLAYER_STATE_V0_SPACE;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
 
 /// Global game configuration account
 #[account]
 #[derive(Default, InitSpace)]
 pub struct GlobalConfig {
+    /// Layout version (see `Versioned`)
+    pub version: u8,
+
     /// Admin authority
     pub admin: Pubkey,
 
-    /// Token mint for the game
-    pub token_mint: Pubkey,
+    /// Proposed admin awaiting acceptance (two-step handover)
+    pub pending_admin: Option<Pubkey>,
 
-    /// Current active round number
-    pub current_round: u64,
+    /// Operator authority (creates/ends/delegates rounds, runs privileged cranks)
+    pub operator: Pubkey,
 
-    /// Total rounds played
-    pub total_rounds: u64,
+    /// Treasurer authority (moves protocol fees)
+    pub treasurer: Pubkey,
+
+    /// Number of arenas created
+    pub total_arenas: u32,
+
+    /// Paused actions bitmask (see `PAUSE_*` constants)
+    pub paused_actions: u8,
+
+    /// Default protocol rake in basis points (snapshotted into new arenas)
+    pub protocol_fee_bps: u16,
+
+    /// Default ephemeral-rollup delegation settings (snapshotted into new arenas)
+    pub delegation: DelegationSettings,
 
     /// Bump seed for PDA
     pub bump: u8,
@@ -23,16 +102,85 @@ pub struct GlobalConfig {
     pub vault_bump: u8,
 }
 
-impl GlobalConfig {}
+impl GlobalConfig {
+    /// Super-admin: configures the game and assigns roles
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admin == *key
+    }
+
+    pub fn is_operator(&self, key: &Pubkey) -> bool {
+        self.operator == *key
+    }
+
+    pub fn is_treasurer(&self, key: &Pubkey) -> bool {
+        self.treasurer == *key
+    }
+
+    pub fn require_not_paused(&self, action: u8) -> Result<()> {
+        require!(self.paused_actions & action == 0, PandaBattleError::Paused);
+        Ok(())
+    }
+}
+
+/// An independent table (own mint, own round counter, own settings) under GlobalConfig
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Arena {
+    /// Reference to global config
+    pub global_config: Pubkey,
+
+    /// Arena index (part of the PDA seeds)
+    pub arena_id: u32,
+
+    /// Human-readable name (UTF-8, zero padded)
+    pub name: [u8; 32],
+
+    /// Token mint rounds in this arena are played with
+    pub token_mint: Pubkey,
+
+    /// Current active round number
+    pub current_round: u64,
+
+    /// Total rounds played
+    pub total_rounds: u64,
+
+    /// Protocol rake on player payments in basis points (snapshotted into new rounds)
+    pub protocol_fee_bps: u16,
+
+    /// Ephemeral-rollup delegation settings (snapshotted into new rounds)
+    pub delegation: DelegationSettings,
+
+    /// Template `start_next_round` creates rounds from (None disables rollover)
+    pub rollover_template: Option<Pubkey>,
+
+    /// Whether rollover waits for the previous round's prizes to be distributed
+    pub rollover_requires_distribution: bool,
+
+    /// PDA bump
+    pub bump: u8,
+}
 
 /// Game round state
 #[account]
 #[derive(Default, InitSpace)]
 pub struct GameRound {
+    /// Layout version (see `Versioned`)
+    pub version: u8,
+
     /// Reference to global config
     pub global_config: Pubkey,
 
-    /// Round number
+    /// Arena this round belongs to (part of the PDA seeds)
+    pub arena: Pubkey,
+
+    /// What the round is paid in (arena token or native SOL), fixed at creation
+    pub currency: Currency,
+
+    /// Token mint the round's vault holds; fixed at creation so arena mint
+    /// changes only affect future rounds (default for native SOL rounds)
+    pub mint: Pubkey,
+
+    /// Round number within the arena
     pub round_number: u64,
 
     /// Entry fee in tokens (e.g., $1.99 worth)
@@ -47,7 +195,25 @@ pub struct GameRound {
     /// Entry fee hourly increase percentage (default: 1%)
     pub entry_hourly_inc_pct: u8,
 
-    /// Round start timestamp
+    /// Reroll fee schedule in tokens (1st, 2nd, 3rd reroll)
+    pub reroll_fees: [u64; MAX_REROLLS],
+
+    /// Maximum number of players (0 = unlimited)
+    pub max_players: u32,
+
+    /// Players required by `min_players_deadline`, or the round is cancelled for refunds (0 = none)
+    pub min_players: u32,
+
+    /// Timestamp by which `min_players` must have joined
+    pub min_players_deadline: i64,
+
+    /// Seconds players have to claim prizes or refunds once the round settles (0 = forever)
+    pub claim_window_secs: i64,
+
+    /// Timestamp after which unclaimed prizes and refunds expire (0 = not settled / no expiry)
+    pub claim_deadline: i64,
+
+    /// Round start timestamp (may be in the future: joins before it are registrations)
     pub start_time: i64,
 
     /// Round end timestamp
@@ -56,29 +222,581 @@ pub struct GameRound {
     /// Leaderboard reveal timestamp (12 hours after start)
     pub leaderboard_reveal_ts: i64,
 
+    /// Balance rules this round is played with
+    pub rules: Pubkey,
+
+    /// Template this round was created from (default for ad-hoc rounds)
+    pub template: Pubkey,
+
+    /// Ephemeral-rollup delegation settings used for this round and its players
+    pub delegation: DelegationSettings,
+
+    /// Merkle root of the wallets allowed to join (None = public round)
+    pub allowlist_root: Option<[u8; 32]>,
+
     /// Total prize pool in tokens
     pub prize_pool: u64,
 
+    /// Part of the prize pool deposited by sponsors (no rake taken)
+    pub sponsored_amount: u64,
+
+    /// Everything players paid in (net of transfer fees, rake included); with
+    /// `sponsored_amount`, the base for pro-rata refunds
+    pub players_paid: u64,
+
+    /// Funds owed back to players and sponsors, snapshotted when the round is cancelled
+    pub refund_pool: u64,
+
+    /// Protocol rake in basis points, fixed at round creation
+    pub protocol_fee_bps: u16,
+
+    /// Accrued protocol rake in tokens (held in the vault, withdrawable by the treasurer)
+    pub protocol_fees: u64,
+
     /// Number of players in this round
     pub player_count: u32,
 
+    /// Player states of this round not yet closed (the round can only be closed at zero)
+    pub open_player_states: u32,
+
+    /// Sponsor records of this round not yet closed (the round can only be closed at zero)
+    pub open_sponsors: u32,
+
     /// Total battles fought this round
     pub total_battles: u32,
 
-    /// Whether the round is active
-    pub is_active: bool,
+    /// Round hour (see `hours_since_start`) of the last hourly jackpot, if any
+    pub last_jackpot_hour: Option<i64>,
+
+    /// Lifecycle status (see `RoundStatus`)
+    pub status: RoundStatus,
+
+    /// Whether expired, unclaimed funds were swept out of the vault
+    pub unclaimed_swept: bool,
+
+    /// Who funded the round and vault accounts; receives their rent back on close
+    pub rent_payer: Pubkey,
+
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl GameRound {
+    /// Apply economic parameters and derive the schedule from the start time
+    pub fn apply_params(&mut self, params: &RoundParams, start_time: i64) -> Result<()> {
+        self.entry_fee = params.entry_fee;
+        self.attack_pack_price = params.attack_pack_price;
+        self.duration_secs = params.duration_secs;
+        self.entry_hourly_inc_pct = params.entry_hourly_inc_pct;
+        self.reroll_fees = params.reroll_fees;
+        self.max_players = params.max_players;
+        self.min_players = params.min_players;
+        self.min_players_deadline = start_time
+            .checked_add(params.min_players_deadline_secs)
+            .ok_or(PandaBattleError::Overflow)?;
+        self.claim_window_secs = params.claim_window_secs;
+        self.start_time = start_time;
+        self.end_time = start_time
+            .checked_add(params.duration_secs)
+            .ok_or(PandaBattleError::Overflow)?;
+        self.leaderboard_reveal_ts = start_time
+            .checked_add(params.duration_secs / 2) // 12 hours for 24h round
+            .ok_or(PandaBattleError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Parameters stay editable until someone joins or the scheduled start passes
+    pub fn params_editable(&self, now: i64) -> bool {
+        self.player_count == 0 || !self.has_started(now)
+    }
+
+    pub fn has_started(&self, now: i64) -> bool {
+        now >= self.start_time
+    }
+
+    /// Status as of `now`: a scheduled round counts as active once its start passes
+    pub fn status_at(&self, now: i64) -> RoundStatus {
+        if self.status == RoundStatus::Scheduled && self.has_started(now) {
+            RoundStatus::Active
+        } else {
+            self.status
+        }
+    }
+
+    /// Persist the time-driven Scheduled -> Active transition
+    pub fn sync_status(&mut self, now: i64) {
+        self.status = self.status_at(now);
+    }
+
+    /// Move the round to `to`, failing if the lifecycle does not allow it
+    pub fn transition_to(&mut self, to: RoundStatus) -> Result<()> {
+        require!(
+            self.status.can_transition_to(to),
+            PandaBattleError::InvalidStatusTransition
+        );
+        self.status = to;
+        Ok(())
+    }
+
+    /// Whether the round still takes players: registration, play or post-reveal play
+    pub fn is_open(&self) -> bool {
+        matches!(
+            self.status,
+            RoundStatus::Scheduled | RoundStatus::Active | RoundStatus::Revealed
+        )
+    }
+
+    /// Whether the round is being played as of `now` (started, not ended or cancelled)
+    pub fn is_live(&self, now: i64) -> bool {
+        matches!(
+            self.status_at(now),
+            RoundStatus::Active | RoundStatus::Revealed
+        )
+    }
+
+    /// Whether the round is full
+    pub fn is_full(&self) -> bool {
+        self.max_players > 0 && self.player_count >= self.max_players
+    }
+
+    /// Whether the minimum-players deadline passed without enough players joining
+    pub fn is_underfilled(&self, now: i64) -> bool {
+        self.min_players > 0
+            && now >= self.min_players_deadline
+            && self.player_count < self.min_players
+    }
+
+    /// Abort the round so players can reclaim what they paid via `claim_refund`.
+    /// The rake (only withdrawable after distribution) goes back towards refunds.
+    pub fn cancel(&mut self, now: i64) -> Result<()> {
+        self.prize_pool = self
+            .prize_pool
+            .checked_add(self.protocol_fees)
+            .ok_or(PandaBattleError::Overflow)?;
+        self.protocol_fees = 0;
+        // Players and sponsors share what is left pro rata, never more than they paid in
+        self.refund_pool = self
+            .prize_pool
+            .min(self.players_paid.saturating_add(self.sponsored_amount));
+        self.transition_to(RoundStatus::Cancelled)?;
+        self.open_claim_window(now);
+
+        Ok(())
+    }
+
+    /// Add deposits made on the base layer while the round was delegated to its pool
+    pub fn fold_pending_deposits(&mut self, pending: &PendingDeposits) -> Result<()> {
+        self.prize_pool = self
+            .prize_pool
+            .checked_add(pending.sponsored)
+            .and_then(|pool| pool.checked_add(pending.carried_over))
+            .ok_or(PandaBattleError::Overflow)?;
+        self.sponsored_amount = self
+            .sponsored_amount
+            .checked_add(pending.sponsored)
+            .ok_or(PandaBattleError::Overflow)?;
+        self.open_sponsors = self
+            .open_sponsors
+            .checked_add(pending.sponsors)
+            .ok_or(PandaBattleError::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Refund owed on a cancelled round for `paid` of the players' and sponsors' deposits
+    pub fn refund_share(&self, paid: u64) -> u64 {
+        let deposits = self.players_paid as u128 + self.sponsored_amount as u128;
+        if deposits == 0 {
+            return 0;
+        }
+        (paid as u128 * self.refund_pool as u128 / deposits) as u64
+    }
+
+    /// Start the claim window once prizes or refunds become claimable
+    pub fn open_claim_window(&mut self, now: i64) {
+        if self.claim_window_secs > 0 {
+            self.claim_deadline = now.saturating_add(self.claim_window_secs);
+        }
+    }
+
+    /// Whether the claim window has elapsed; unclaimed funds can then be swept
+    pub fn claims_expired(&self, now: i64) -> bool {
+        self.claim_deadline > 0 && now >= self.claim_deadline
+    }
+
+    /// Whether prizes or refunds are final, so the round's accounts can be closed
+    pub fn is_settled(&self) -> bool {
+        matches!(
+            self.status,
+            RoundStatus::Distributed | RoundStatus::Cancelled
+        )
+    }
+
+    /// Whole hours elapsed since the scheduled start (0 during registration)
+    pub fn hours_since_start(&self, now: i64) -> i64 {
+        (now - self.start_time).max(0) / 3600
+    }
+
+    /// Fee for the next reroll given how many rerolls were already used
+    pub fn reroll_fee(&self, rerolls_used: u8) -> Option<u64> {
+        self.reroll_fees.get(rerolls_used as usize).copied()
+    }
+
+    /// Check that `player` may join and return its proven entry-fee discount (bps).
+    /// Leaves are `hash(0x00 || player || discount_bps as u16 LE)`; inner nodes are
+    /// `hash(0x01 || min(a, b) || max(a, b))`. Public rounds accept no discount.
+    pub fn check_allowlist(
+        &self,
+        player: &Pubkey,
+        discount_bps: u16,
+        proof: &[[u8; 32]],
+    ) -> Result<u16> {
+        let Some(root) = self.allowlist_root else {
+            require!(discount_bps == 0, PandaBattleError::NotAllowlisted);
+            return Ok(0);
+        };
+        require!(discount_bps <= 10000, PandaBattleError::InvalidConfig);
+
+        let leaf = hashv(&[
+            ALLOWLIST_LEAF_PREFIX,
+            player.as_ref(),
+            &discount_bps.to_le_bytes(),
+        ])
+        .to_bytes();
+        let computed = proof.iter().fold(leaf, |node, sibling| {
+            let (left, right) = if node <= *sibling {
+                (node, *sibling)
+            } else {
+                (*sibling, node)
+            };
+            hashv(&[ALLOWLIST_NODE_PREFIX, &left, &right]).to_bytes()
+        });
+
+        require!(computed == root, PandaBattleError::NotAllowlisted);
+        Ok(discount_bps)
+    }
+
+    /// Split a player payment into protocol rake and prize pool. Returns the rake.
+    pub fn credit_payment(&mut self, amount: u64) -> Result<u64> {
+        let rake = (amount as u128 * self.protocol_fee_bps as u128 / 10000) as u64;
+
+        self.players_paid = self
+            .players_paid
+            .checked_add(amount)
+            .ok_or(PandaBattleError::Overflow)?;
+        self.protocol_fees = self
+            .protocol_fees
+            .checked_add(rake)
+            .ok_or(PandaBattleError::Overflow)?;
+        self.prize_pool = self
+            .prize_pool
+            .checked_add(amount - rake)
+            .ok_or(PandaBattleError::Overflow)?;
+
+        Ok(rake)
+    }
+}
+
+/// Balance rules for a round (turns, progression, battle and payout tuning)
+#[account]
+#[derive(Default, InitSpace)]
+pub struct RoundRules {
+    /// Reference to global config
+    pub global_config: Pubkey,
+
+    /// Rules identifier (part of the PDA seeds)
+    pub rules_id: u32,
+
+    /// Maximum turns a player can hold
+    pub max_turns: u8,
+
+    /// Starting turns when joining a round
+    pub starting_turns: u8,
+
+    /// Turns regenerated per hour
+    pub turns_per_hour: u8,
 
-    /// Whether payouts have been processed
-    pub payouts_processed: bool,
+    /// XP thresholds for each level (cumulative)
+    pub level_xp_thresholds: [u32; 11],
+
+    /// XP gained per win
+    pub xp_per_win: u32,
+
+    /// Maximum attribute cap
+    pub max_attribute: u8,
+
+    /// Maximum battle turns
+    pub max_battle_turns: u8,
+
+    /// Crit damage bonus in basis points (5000 = +50%)
+    pub crit_damage_multiplier: u16,
+
+    /// Share of the prize pool paid to the leaderboard (percentage, e.g. 80)
+    pub top_pool_pct: u8,
+
+    /// Number of leaderboard entries (top N players)
+    pub leaderboard_size: u8,
 
     /// Bump seed for PDA
     pub bump: u8,
 }
 
+impl RoundRules {
+    pub fn apply(&mut self, params: &RoundRulesParams) -> Result<()> {
+        params.validate()?;
+
+        self.max_turns = params.max_turns;
+        self.starting_turns = params.starting_turns;
+        self.turns_per_hour = params.turns_per_hour;
+        self.level_xp_thresholds = params.level_xp_thresholds;
+        self.xp_per_win = params.xp_per_win;
+        self.max_attribute = params.max_attribute;
+        self.max_battle_turns = params.max_battle_turns;
+        self.crit_damage_multiplier = params.crit_damage_multiplier;
+        self.top_pool_pct = params.top_pool_pct;
+        self.leaderboard_size = params.leaderboard_size;
+
+        Ok(())
+    }
+}
+
+/// Round rules parameters (see `RoundRules` for field meanings)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RoundRulesParams {
+    pub max_turns: u8,
+    pub starting_turns: u8,
+    pub turns_per_hour: u8,
+    pub level_xp_thresholds: [u32; 11],
+    pub xp_per_win: u32,
+    pub max_attribute: u8,
+    pub max_battle_turns: u8,
+    pub crit_damage_multiplier: u16,
+    pub top_pool_pct: u8,
+    pub leaderboard_size: u8,
+}
+
+impl RoundRulesParams {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.max_turns > 0, PandaBattleError::InvalidConfig);
+        require!(
+            self.starting_turns <= self.max_turns,
+            PandaBattleError::InvalidConfig
+        );
+        require!(
+            self.level_xp_thresholds[0] == 0
+                && self.level_xp_thresholds.windows(2).all(|w| w[0] < w[1]),
+            PandaBattleError::InvalidConfig
+        );
+        require!(
+            self.max_attribute >= BASE_ATTRIBUTE_MAX,
+            PandaBattleError::InvalidConfig
+        );
+        require!(self.max_battle_turns > 0, PandaBattleError::InvalidConfig);
+        require!(self.top_pool_pct <= 100, PandaBattleError::InvalidConfig);
+        require!(
+            self.leaderboard_size > 0 && self.leaderboard_size as usize <= MAX_LEADERBOARD_SIZE,
+            PandaBattleError::InvalidConfig
+        );
+
+        Ok(())
+    }
+}
+
+/// Ephemeral-rollup validator and commit frequency used when delegating accounts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct DelegationSettings {
+    /// ER validator to delegate to (None lets the delegation program pick)
+    pub validator: Option<Pubkey>,
+
+    /// How often the ER commits state back to the base layer
+    pub commit_frequency_ms: u32,
+}
+
+impl DelegationSettings {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.commit_frequency_ms > 0,
+            PandaBattleError::InvalidConfig
+        );
+        Ok(())
+    }
+
+    pub fn delegate_config(&self) -> DelegateConfig {
+        DelegateConfig {
+            commit_frequency_ms: self.commit_frequency_ms,
+            validator: self.validator,
+        }
+    }
+}
+
+/// Round economic parameters (see `GameRound` for field meanings)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RoundParams {
+    pub entry_fee: u64,
+    pub attack_pack_price: u64,
+    pub duration_secs: i64,
+    pub entry_hourly_inc_pct: u8,
+    pub reroll_fees: [u64; MAX_REROLLS],
+    pub max_players: u32,
+    pub min_players: u32,
+    /// Seconds after the start by which `min_players` must have joined
+    pub min_players_deadline_secs: i64,
+    /// Seconds to claim prizes or refunds once the round settles (0 = no expiry)
+    pub claim_window_secs: i64,
+}
+
+impl RoundParams {
+    /// Duration must be positive; reroll fees must be non-decreasing and at least
+    /// 1/MIN_REROLL_FEE_DIVISOR of a whole token; a player cap must fit the minimum,
+    /// whose deadline falls within the round; the claim window can't be negative
+    pub fn validate(&self, decimals: u8) -> Result<()> {
+        require!(self.duration_secs > 0, PandaBattleError::InvalidConfig);
+        require!(self.claim_window_secs >= 0, PandaBattleError::InvalidConfig);
+        require!(
+            self.max_players == 0 || self.max_players >= self.min_players,
+            PandaBattleError::InvalidConfig
+        );
+        require!(
+            self.min_players == 0
+                || (self.min_players_deadline_secs > 0
+                    && self.min_players_deadline_secs <= self.duration_secs),
+            PandaBattleError::InvalidConfig
+        );
+
+        let one_token = 10u64
+            .checked_pow(decimals as u32)
+            .ok_or(PandaBattleError::InvalidConfig)?;
+        let min_fee = (one_token / MIN_REROLL_FEE_DIVISOR).max(1);
+
+        require!(
+            self.reroll_fees.iter().all(|fee| *fee >= min_fee),
+            PandaBattleError::RerollFeeTooLow
+        );
+        require!(
+            self.reroll_fees.windows(2).all(|w| w[0] <= w[1]),
+            PandaBattleError::InvalidConfig
+        );
+
+        Ok(())
+    }
+}
+
+/// Named, reusable round configuration so recurring rounds are reproducible on-chain
+#[account]
+#[derive(InitSpace)]
+pub struct RoundTemplate {
+    /// Reference to global config
+    pub global_config: Pubkey,
+
+    /// Template index (part of the PDA seeds)
+    pub template_id: u32,
+
+    /// Human-readable name (UTF-8, zero padded)
+    pub name: [u8; 32],
+
+    /// What rounds from this template are paid in
+    pub currency: Currency,
+
+    /// Mint the prices were validated against (token templates only); only arenas
+    /// using it can use the template
+    pub mint: Pubkey,
+
+    /// Economic parameters copied into each round
+    pub params: RoundParams,
+
+    /// Balance rules rounds from this template are played with
+    pub rules: Pubkey,
+
+    /// Number of rounds created from this template
+    pub rounds_created: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+impl RoundTemplate {
+    /// SOL templates fit any arena; token templates only the arena on their mint
+    pub fn fits_arena(&self, arena: &Arena) -> bool {
+        self.currency == Currency::NativeSol || self.mint == arena.token_mint
+    }
+}
+
+/// Program-owned SOL account that reimburses `start_next_round` callers for rent
+/// and delegation costs. Funded by plain SOL transfers to its address.
+#[account]
+#[derive(InitSpace)]
+pub struct RolloverVault {
+    /// Reference to global config
+    pub global_config: Pubkey,
+
+    /// Total lamports reimbursed to crank callers
+    pub total_reimbursed: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+/// Program-owned vault holding the lamports of a native SOL round
+#[account]
+#[derive(InitSpace)]
+pub struct SolVault {
+    /// Round this vault belongs to
+    pub game_round: Pubkey,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+/// A contributor's sponsorship of a round's prize pool
+#[account]
+#[derive(InitSpace)]
+pub struct Sponsor {
+    /// Round being sponsored
+    pub round: Pubkey,
+
+    /// Contributor wallet
+    pub sponsor: Pubkey,
+
+    /// Total deposited by this contributor (net of transfer fees)
+    pub amount: u64,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
+/// Funds deposited into a round's vault on the base layer. The round itself is usually
+/// delegated there, so it can't be credited directly; the totals are folded into its
+/// pool when it is distributed or cancelled.
+#[account]
+#[derive(InitSpace)]
+pub struct PendingDeposits {
+    /// Round the deposits were made into
+    pub round: Pubkey,
+
+    /// Sponsor deposits (net of transfer fees)
+    pub sponsored: u64,
+
+    /// Sponsor records created for the round
+    pub sponsors: u32,
+
+    /// Unclaimed funds swept in from the previous round
+    pub carried_over: u64,
+
+    /// Who funded this account; receives its rent back when the round closes
+    pub rent_payer: Pubkey,
+
+    /// PDA bump
+    pub bump: u8,
+}
+
 /// Player state for a specific round
 #[account]
 #[derive(Default, InitSpace)]
 pub struct PlayerState {
+    /// Layout version (see `Versioned`)
+    pub version: u8,
+
     /// Player's wallet address
     pub player: Pubkey,
 
@@ -146,6 +864,9 @@ pub struct PlayerState {
     /// Whether prize has been claimed
     pub prize_claimed: bool,
 
+    /// Whether the unclaimed prize or refund expired and was swept
+    pub claim_expired: bool,
+
     // ===== TIMESTAMPS =====
     /// When player joined the round
     pub joined_at: i64,
@@ -153,6 +874,12 @@ pub struct PlayerState {
     /// Entry fee paid (for tracking)
     pub entry_fee_paid: u64,
 
+    /// Total spent on attack packs (refundable if the round is cancelled)
+    pub packs_spent: u64,
+
+    /// Total spent on rerolls (refundable if the round is cancelled)
+    pub rerolls_spent: u64,
+
     /// Bump seed for PDA
     pub bump: u8,
 }
@@ -167,6 +894,32 @@ impl PlayerState {
     pub fn calculate_hp(&self) -> u16 {
         100 + ((self.str as u16 + self.int as u16) * 2)
     }
+
+    /// Everything paid into the round: entry fee, attack packs and rerolls
+    pub fn total_paid(&self) -> u64 {
+        self.entry_fee_paid
+            .saturating_add(self.packs_spent)
+            .saturating_add(self.rerolls_spent)
+    }
+
+    /// Refund owed from a cancelled round: the player's pro-rata share of the refund
+    /// pool, so players and sponsors lose the same fraction if the pool fell short
+    pub fn refund_amount(&self, round: &GameRound) -> u64 {
+        round.refund_share(self.total_paid())
+    }
+
+    /// Whether the player has nothing left to claim from a settled round:
+    /// the prize or refund was claimed, expired and swept, or it is zero
+    pub fn has_nothing_to_claim(&self, round: &GameRound) -> bool {
+        if self.prize_claimed || self.claim_expired || round.unclaimed_swept {
+            return true;
+        }
+        if round.status == RoundStatus::Cancelled {
+            self.refund_amount(round) == 0
+        } else {
+            self.prize_share == 0
+        }
+    }
 }
 
 /// Leaderboard entry
@@ -176,15 +929,15 @@ pub struct LeaderboardEntry {
     pub points: u16,
 }
 
-/// Leaderboard state (top 20 players)
+/// Leaderboard state (top N players, up to 20)
 #[account]
 #[derive(Default, InitSpace)]
 pub struct Leaderboard {
     /// Reference to the game round
     pub round: Pubkey,
 
-    /// Top 20 players (sorted by points descending)
-    #[max_len(20)]
+    /// Top players (sorted by points descending)
+    #[max_len(MAX_LEADERBOARD_SIZE)]
     pub entries: Vec<LeaderboardEntry>,
 
     /// Whether leaderboard has been revealed
@@ -194,17 +947,59 @@ pub struct Leaderboard {
     pub bump: u8,
 }
 
-/// Round status enum
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+/// What a round's entry fees, packs, rerolls and prizes are paid in
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default, InitSpace, Debug,
+)]
+pub enum Currency {
+    /// The arena's SPL / Token-2022 mint, held in the round's vault ATA
+    #[default]
+    Token,
+    /// Lamports, held in the round's `SolVault` PDA
+    NativeSol,
+}
+
+/// Lifecycle of a game round: Scheduled -> Active -> Revealed -> Ended -> Distributed ->
+/// Closed. Rounds may end before the leaderboard is revealed, and may be cancelled (refunds
+/// instead of prizes) at any point before distribution. An empty active round can be
+/// rescheduled. Distributed and cancelled rounds move to Closed as `close_round` deletes
+/// them.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default, InitSpace,
+)]
 pub enum RoundStatus {
-    Pending,
+    /// Created; joins are registrations until the start time
+    #[default]
+    Scheduled,
+    /// Started; battles and purchases are open
     Active,
+    /// Leaderboard revealed; play continues and hourly jackpots run
+    Revealed,
+    /// Ended; waiting for prize distribution
     Ended,
-    PayoutsProcessed,
+    /// Prize shares assigned; players claim prizes
+    Distributed,
+    /// Aborted; players claim refunds
+    Cancelled,
+    /// Deleted by `close_round`
+    Closed,
 }
 
-impl Default for RoundStatus {
-    fn default() -> Self {
-        RoundStatus::Pending
+impl RoundStatus {
+    /// Whether the lifecycle allows moving from this status to `to`
+    pub fn can_transition_to(self, to: RoundStatus) -> bool {
+        use RoundStatus::*;
+
+        matches!(
+            (self, to),
+            (Scheduled, Active)
+                // An empty round whose start `update_round_params` moved into the future
+                | (Active, Scheduled)
+                | (Active, Revealed)
+                | (Active | Revealed, Ended)
+                | (Ended, Distributed)
+                | (Scheduled | Active | Revealed | Ended, Cancelled)
+                | (Distributed | Cancelled, Closed)
+        )
     }
 }
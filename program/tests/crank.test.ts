@@ -1,7 +1,13 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { PandaBattle } from "../target/types/panda_battle";
-import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  Transaction,
+  LAMPORTS_PER_SOL,
+} from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -14,13 +20,21 @@ import { assert } from "chai";
 import {
   airdrop,
   getGlobalConfigPDA,
+  getArenaPDA,
   getGameRoundPDA,
+  getRoundRulesPDA,
+  getRoundTemplatePDA,
+  getRolloverVaultPDA,
   getPlayerStatePDA,
+  getSponsorPDA,
   getLeaderboardPDA,
+  getPendingDepositsPDA,
   getGlobalConfig,
+  getArena,
   getGameRound,
   getPlayerState,
   getLeaderboard,
+  getPendingDeposits,
 } from "./utils";
 
 describe("Crank Instructions", () => {
@@ -29,8 +43,12 @@ describe("Crank Instructions", () => {
 
   const program = anchor.workspace.PandaBattle as Program<PandaBattle>;
   const admin = provider.wallet as anchor.Wallet;
+  const DELEGATION_PROGRAM_ID = new PublicKey(
+    "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+  );
 
   let globalConfigPDA: PublicKey;
+  let arenaPDA: PublicKey;
   let mint: PublicKey;
   let roundPDA: PublicKey;
   let vaultPDA: PublicKey;
@@ -41,6 +59,7 @@ describe("Crank Instructions", () => {
   let player1StatePDA: PublicKey;
 
   let cranker: Keypair;
+  let jackpotPaid = false;
 
   before(async () => {
     // Setup cranker
@@ -80,7 +99,7 @@ describe("Crank Instructions", () => {
     // Initialize game
     globalConfigPDA = getGlobalConfigPDA(program);
     await program.methods
-      .initializeGame(mint)
+      .initializeGame()
       .accountsPartial({
         admin: admin.publicKey,
         globalConfig: globalConfigPDA,
@@ -88,23 +107,49 @@ describe("Crank Instructions", () => {
       })
       .rpc();
 
+    // Create arena
+    arenaPDA = getArenaPDA(program, globalConfigPDA, 3);
+    await program.methods
+      .createArena(3, Array(32).fill(0))
+      .accountsPartial({
+        admin: admin.publicKey,
+        globalConfig: globalConfigPDA,
+        mint: mint,
+        arena: arenaPDA,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
     // Create round
-    roundPDA = getGameRoundPDA(program, globalConfigPDA, 1);
+    roundPDA = getGameRoundPDA(program, arenaPDA, 1);
     vaultPDA = await getAssociatedTokenAddress(mint, roundPDA, true);
     leaderboardPDA = getLeaderboardPDA(program, roundPDA);
 
     await program.methods
       .createRound(
-        new BN(1_990_000),
-        new BN(100_000),
-        new BN(86400),
-        1
+        {
+          entryFee: new BN(1_990_000),
+          attackPackPrice: new BN(100_000),
+          durationSecs: new BN(86400),
+          entryHourlyIncPct: 1,
+          rerollFees: [new BN(1_000_000), new BN(1_000_000), new BN(1_000_000)],
+          maxPlayers: 0, // no cap
+          minPlayers: 0,
+          minPlayersDeadlineSecs: new BN(0),
+          claimWindowSecs: new BN(0), // claims never expire
+        },
+        null,
+        null,
+        { token: {} }
       )
       .accountsPartial({
-        admin: admin.publicKey,
+        operator: admin.publicKey,
         mint: mint,
         globalConfig: globalConfigPDA,
+        arena: arenaPDA,
+        roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
         gameRound: roundPDA,
+        leaderboard: leaderboardPDA,
         vault: vaultPDA,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -123,6 +168,7 @@ describe("Crank Instructions", () => {
           caller: cranker.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
           playerState: player1StatePDA,
         })
         .signers([cranker])
@@ -164,8 +210,8 @@ describe("Crank Instructions", () => {
           caller: cranker.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
           leaderboard: leaderboardPDA,
-          systemProgram: SystemProgram.programId,
         })
         .remainingAccounts([
           // Add player state accounts here
@@ -201,34 +247,105 @@ describe("Crank Instructions", () => {
       await program.methods
         .hourlyJackpot(clientSeed)
         .accountsPartial({
-          caller: cranker.publicKey,
+          operator: admin.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          mint: mint,
           leaderboard: leaderboardPDA,
           vault: vaultPDA,
+          winner: player1.publicKey,
           winnerTokenAccount: winnerTokenAccount,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
-        .signers([cranker])
         .rpc();
 
+      jackpotPaid = true;
       console.log("Hourly jackpot distributed");
     } catch (err: any) {
       console.log("Hourly jackpot failed:", err.message);
     }
   });
 
+  it("Hourly jackpot pays once per hour", async function () {
+    if (!jackpotPaid) {
+      this.skip();
+    }
+
+    try {
+      await program.methods
+        .hourlyJackpot(78)
+        .accountsPartial({
+          operator: admin.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          mint: mint,
+          leaderboard: leaderboardPDA,
+          vault: vaultPDA,
+          winner: player1.publicKey,
+          winnerTokenAccount: await getAssociatedTokenAddress(
+            mint,
+            player1.publicKey
+          ),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Second jackpot in the same hour should fail");
+    } catch (err: any) {
+      assert.include(err.message, "JackpotAlreadyPaid");
+    }
+  });
+
+  it("Cancel underfilled round", async () => {
+    // This round has no minimum, so it can never be cancelled as underfilled
+    try {
+      await program.methods
+        .cancelUnderfilledRound()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          pendingDeposits: getPendingDepositsPDA(program, roundPDA),
+        })
+        .signers([cranker])
+        .rpc();
+      assert.fail("Round without min_players should not be cancellable");
+    } catch (err: any) {
+      assert.include(err.message, "MinPlayersReached");
+    }
+  });
+
   it("Distribute prizes", async () => {
     // End round first
     await program.methods
       .endRound()
       .accountsPartial({
-        admin: admin.publicKey,
+        caller: admin.publicKey,
         globalConfig: globalConfigPDA,
         gameRound: roundPDA,
       })
       .rpc();
 
+    // Every player state of the round must be passed, or the rest pool would be split
+    // among too few players
+    try {
+      await program.methods
+        .distributePrizes()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
+          leaderboard: leaderboardPDA,
+          pendingDeposits: getPendingDepositsPDA(program, roundPDA),
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([cranker])
+        .rpc();
+      assert.fail("Distribution without the round's player states should fail");
+    } catch (err: any) {
+      assert.match(err.message, /PlayerStatesMismatch|LeaderboardNotRevealed/);
+    }
+
     try {
       await program.methods
         .distributePrizes()
@@ -236,11 +353,13 @@ describe("Crank Instructions", () => {
           caller: cranker.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
           leaderboard: leaderboardPDA,
+          pendingDeposits: getPendingDepositsPDA(program, roundPDA),
           systemProgram: SystemProgram.programId,
         })
         .remainingAccounts([
-          // Add all player state accounts here
+          // Every player state of the round, each exactly once
           {
             pubkey: player1StatePDA,
             isWritable: true,
@@ -251,10 +370,375 @@ describe("Crank Instructions", () => {
         .rpc();
 
       const gameRound = await getGameRound(program, roundPDA);
-      assert.equal(gameRound.payoutsProcessed, true);
+      assert.deepEqual(gameRound.status, { distributed: {} });
       console.log("Prizes distributed successfully");
     } catch (err: any) {
       console.log("Prize distribution failed:", err.message);
     }
   });
+
+  it("Commit round state includes the leaderboard", async () => {
+    // Checkpoints cover the round, its leaderboard and the player states passed
+    const commitRoundState = program.idl.instructions.find(
+      (ix) => ix.name === "commit_round_state"
+    );
+    assert.includeMembers(
+      commitRoundState.accounts.map((account) => account.name),
+      ["game_round", "leaderboard", "magic_context", "magic_program"]
+    );
+    const leaderboardAccount = commitRoundState.accounts.find(
+      (account) => account.name === "leaderboard"
+    );
+    assert.isTrue("writable" in leaderboardAccount && leaderboardAccount.writable);
+
+    // Commits only run in the rollup; on the base layer the delegated round is rejected
+    try {
+      await program.methods
+        .commitRoundState()
+        .accountsPartial({
+          payer: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          leaderboard: leaderboardPDA,
+        })
+        .remainingAccounts([
+          {
+            pubkey: player1StatePDA,
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+        .signers([cranker])
+        .rpc();
+      assert.fail("Committing a delegated round from the base layer should fail");
+    } catch (err: any) {
+      // If the round was already handed back, the magic program (not deployed on
+      // localnet) is rejected instead
+      assert.match(
+        err.message,
+        /AccountOwnedByWrongProgram|InvalidProgramExecutable|InvalidProgramId/
+      );
+    }
+  });
+
+  it("Settle round undelegates the leaderboard with the round", async () => {
+    // The round and its leaderboard are delegated together, so they come back together
+    const settleRound = program.idl.instructions.find(
+      (ix) => ix.name === "settle_round"
+    );
+    assert.includeMembers(
+      settleRound.accounts.map((account) => account.name),
+      ["game_round", "leaderboard", "magic_context", "magic_program"]
+    );
+    const leaderboardAccount = settleRound.accounts.find(
+      (account) => account.name === "leaderboard"
+    );
+    assert.isTrue("writable" in leaderboardAccount && leaderboardAccount.writable);
+
+    // Only the rollup can write the delegated round; on the base layer it is rejected
+    // before anything is committed
+    try {
+      await program.methods
+        .settleRound(true)
+        .accountsPartial({
+          payer: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          leaderboard: leaderboardPDA,
+        })
+        .remainingAccounts([
+          {
+            pubkey: player1StatePDA,
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+        .signers([cranker])
+        .rpc();
+      assert.fail("Settling a delegated round from the base layer should fail");
+    } catch (err: any) {
+      // RoundNotSettled if the round was handed back before prizes were distributed
+      assert.match(err.message, /AccountOwnedByWrongProgram|RoundNotSettled/);
+    }
+
+    // Another round's leaderboard doesn't match the seeds
+    try {
+      await program.methods
+        .settleRound(true)
+        .accountsPartial({
+          payer: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          leaderboard: getLeaderboardPDA(
+            program,
+            getGameRoundPDA(program, arenaPDA, 2)
+          ),
+        })
+        .signers([cranker])
+        .rpc();
+      assert.fail("Settling with another round's leaderboard should fail");
+    } catch (err: any) {
+      assert.match(
+        err.message,
+        /ConstraintSeeds|AccountOwnedByWrongProgram|AccountNotInitialized|RoundNotSettled/
+      );
+    }
+  });
+
+  it("Start next round", async () => {
+    const roundTemplatePDA = getRoundTemplatePDA(program, globalConfigPDA, 1);
+    const rolloverVaultPDA = getRolloverVaultPDA(program, globalConfigPDA);
+
+    try {
+      await program.methods
+        .setRollover(false)
+        .accountsPartial({
+          admin: admin.publicKey,
+          globalConfig: globalConfigPDA,
+          arena: arenaPDA,
+          roundTemplate: roundTemplatePDA,
+          rolloverVault: rolloverVaultPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Fund the rollover vault with a plain SOL transfer
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: admin.publicKey,
+            toPubkey: rolloverVaultPDA,
+            lamports: LAMPORTS_PER_SOL,
+          })
+        )
+      );
+
+      const arena = await getArena(program, arenaPDA);
+      const nextRoundPDA = getGameRoundPDA(
+        program,
+        arenaPDA,
+        arena.totalRounds.add(new BN(1)).toNumber()
+      );
+      const [bufferPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("buffer"), nextRoundPDA.toBuffer()],
+        program.programId
+      );
+      const [delegationRecordPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("delegation"), nextRoundPDA.toBuffer()],
+        DELEGATION_PROGRAM_ID
+      );
+      const [delegationMetadataPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("delegation-metadata"), nextRoundPDA.toBuffer()],
+        DELEGATION_PROGRAM_ID
+      );
+
+      await program.methods
+        .startNextRound()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          arena: arenaPDA,
+          previousRound: getGameRoundPDA(
+            program,
+            arenaPDA,
+            arena.currentRound.toNumber()
+          ),
+          roundTemplate: roundTemplatePDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
+          mint: mint,
+          gameRound: nextRoundPDA,
+          leaderboard: getLeaderboardPDA(program, nextRoundPDA),
+          vault: await getAssociatedTokenAddress(mint, nextRoundPDA, true),
+          solVault: null,
+          rolloverVault: rolloverVaultPDA,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          ownerProgram: program.programId,
+          bufferAccount: bufferPda,
+          delegationRecordAccount: delegationRecordPda,
+          delegationMetadataAccount: delegationMetadataPda,
+          delegationProgram: DELEGATION_PROGRAM_ID,
+        })
+        .signers([cranker])
+        .rpc();
+
+      const nextRound = await getGameRound(program, nextRoundPDA);
+      assert.equal(nextRound.template.toString(), roundTemplatePDA.toString());
+      console.log("Next round started by crank");
+    } catch (err: any) {
+      console.log("Start next round failed:", err.message);
+    }
+  });
+
+  it("Sponsor a delegated round", async function () {
+    // The round started by the rollover above is delegated, so the base layer can't write
+    // it: the sponsorship is recorded in its pending deposits until the round settles
+    const nextRoundPDA = getGameRoundPDA(program, arenaPDA, 2);
+    const nextRoundInfo = await provider.connection.getAccountInfo(nextRoundPDA);
+    if (!nextRoundInfo || !nextRoundInfo.owner.equals(DELEGATION_PROGRAM_ID)) {
+      this.skip(); // the rollover above did not run
+    }
+
+    const amount = new BN(2_000_000);
+    const pendingDepositsPDA = getPendingDepositsPDA(program, nextRoundPDA);
+    const roundBefore = await getGameRound(program, nextRoundPDA);
+
+    await program.methods
+      .sponsorRound(new BN(2), amount)
+      .accountsPartial({
+        sponsor: player1.publicKey,
+        globalConfig: globalConfigPDA,
+        arena: arenaPDA,
+        gameRound: nextRoundPDA,
+        pendingDeposits: pendingDepositsPDA,
+        sponsorRecord: getSponsorPDA(program, nextRoundPDA, player1.publicKey),
+        mint: mint,
+        sponsorTokenAccount: player1TokenAccount,
+        vault: await getAssociatedTokenAddress(mint, nextRoundPDA, true),
+        solVault: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([player1])
+      .rpc();
+
+    const pending = await getPendingDeposits(program, pendingDepositsPDA);
+    assert.equal(pending.round.toString(), nextRoundPDA.toString());
+    assert.equal(pending.sponsored.toString(), amount.toString());
+    assert.equal(pending.sponsors, 1);
+    assert.equal(pending.rentPayer.toString(), player1.publicKey.toString());
+
+    // Still delegated and untouched until it is distributed or cancelled
+    const roundInfo = await provider.connection.getAccountInfo(nextRoundPDA);
+    assert.isTrue(roundInfo.owner.equals(DELEGATION_PROGRAM_ID));
+    const roundAfter = await getGameRound(program, nextRoundPDA);
+    assert.equal(roundAfter.prizePool.toString(), roundBefore.prizePool.toString());
+  });
+
+  it("Sweep unclaimed", async () => {
+    // This round has no claim window, so its prizes never expire
+    const globalConfig = await getGlobalConfig(program, globalConfigPDA);
+    try {
+      await program.methods
+        .sweepUnclaimed()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          nextRound: getGameRoundPDA(program, arenaPDA, 2),
+          nextPendingDeposits: null,
+          mint: mint,
+          vault: vaultPDA,
+          solVault: null,
+          nextVault: null,
+          nextSolVault: null,
+          treasurer: globalConfig.treasurer,
+          treasuryTokenAccount: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: player1StatePDA, isWritable: true, isSigner: false },
+        ])
+        .signers([cranker])
+        .rpc();
+      assert.fail("Round without a claim window should not be sweepable");
+    } catch (err: any) {
+      // RoundNotSettled if prize distribution did not go through above
+      assert.match(err.message, /ClaimWindowOpen|RoundNotSettled/);
+    }
+  });
+
+  it("Sweep unclaimed accepts a still-delegated next round", async function () {
+    // The round started by the rollover above is delegated, so on the base layer the
+    // delegation program owns it: the sweep must still accept it (and pay the treasury)
+    const nextRoundPDA = getGameRoundPDA(program, arenaPDA, 2);
+    const nextRoundInfo = await provider.connection.getAccountInfo(nextRoundPDA);
+    if (!nextRoundInfo) {
+      this.skip(); // the rollover above did not run
+    }
+
+    const globalConfig = await getGlobalConfig(program, globalConfigPDA);
+    try {
+      await program.methods
+        .sweepUnclaimed()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          nextRound: nextRoundPDA,
+          nextPendingDeposits: null,
+          mint: mint,
+          vault: vaultPDA,
+          solVault: null,
+          nextVault: null,
+          nextSolVault: null,
+          treasurer: globalConfig.treasurer,
+          treasuryTokenAccount: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([cranker])
+        .rpc();
+      assert.fail("Round without a claim window should not be sweepable");
+    } catch (err: any) {
+      // Past account validation: the handler rejects the unexpired round, not the owner
+      assert.match(err.message, /ClaimWindowOpen|RoundNotSettled/);
+    }
+  });
+
+  it("Close player state and round", async () => {
+    // Leftover vault funds are swept to the treasurer
+    const globalConfig = await getGlobalConfig(program, globalConfigPDA);
+    const treasuryTokenAccount = await getAssociatedTokenAddress(
+      mint,
+      globalConfig.treasurer
+    );
+
+    try {
+      await program.methods
+        .closePlayerState()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: roundPDA,
+          playerState: player1StatePDA,
+          player: player1.publicKey,
+        })
+        .signers([cranker])
+        .rpc();
+
+      const gameRound = await getGameRound(program, roundPDA);
+      assert.equal(gameRound.openPlayerStates, 0);
+
+      await program.methods
+        .closeRound()
+        .accountsPartial({
+          caller: cranker.publicKey,
+          globalConfig: globalConfigPDA,
+          arena: arenaPDA,
+          gameRound: roundPDA,
+          rentPayer: gameRound.rentPayer,
+          leaderboard: leaderboardPDA,
+          pendingDeposits: getPendingDepositsPDA(program, roundPDA),
+          pendingDepositsRentPayer: null,
+          treasurer: globalConfig.treasurer,
+          mint: mint,
+          vault: vaultPDA,
+          solVault: null,
+          treasuryTokenAccount,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([cranker])
+        .rpc();
+
+      const closed = await provider.connection.getAccountInfo(roundPDA);
+      assert.isNull(closed);
+      console.log("Player state and round closed");
+    } catch (err: any) {
+      console.log("Close round failed:", err.message);
+    }
+  });
 });
@@ -4,21 +4,31 @@ import { PandaBattle } from "../target/types/panda_battle";
 import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   createMint,
   getAssociatedTokenAddress,
   createAssociatedTokenAccount,
+  getAccount,
   mintTo,
 } from "@solana/spl-token";
 import { assert } from "chai";
 import {
   airdrop,
   getGlobalConfigPDA,
+  getArenaPDA,
   getGameRoundPDA,
+  getLeaderboardPDA,
+  getRoundRulesPDA,
   getPlayerStatePDA,
+  getSponsorPDA,
+  getPendingDepositsPDA,
+  createTransferFeeMint,
   getGlobalConfig,
+  getArena,
   getGameRound,
   getPlayerState,
+  getPendingDeposits,
 } from "./utils";
 
 // Mock VRF oracle queue (use default from ephemeral-vrf-sdk)
@@ -32,6 +42,7 @@ describe("Player Instructions", () => {
   const admin = provider.wallet as anchor.Wallet;
 
   let globalConfigPDA: PublicKey;
+  let arenaPDA: PublicKey;
   let mint: PublicKey;
   let roundPDA: PublicKey;
   let vaultPDA: PublicKey;
@@ -95,7 +106,7 @@ describe("Player Instructions", () => {
     // Initialize game
     globalConfigPDA = getGlobalConfigPDA(program);
     await program.methods
-      .initializeGame(mint)
+      .initializeGame()
       .accountsPartial({
         admin: admin.publicKey,
         globalConfig: globalConfigPDA,
@@ -103,22 +114,48 @@ describe("Player Instructions", () => {
       })
       .rpc();
 
+    // Create arena
+    arenaPDA = getArenaPDA(program, globalConfigPDA, 2);
+    await program.methods
+      .createArena(2, Array(32).fill(0))
+      .accountsPartial({
+        admin: admin.publicKey,
+        globalConfig: globalConfigPDA,
+        mint: mint,
+        arena: arenaPDA,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
     // Create round
-    roundPDA = getGameRoundPDA(program, globalConfigPDA, 1);
+    roundPDA = getGameRoundPDA(program, arenaPDA, 1);
     vaultPDA = await getAssociatedTokenAddress(mint, roundPDA, true);
 
     await program.methods
       .createRound(
-        new BN(1_990_000),
-        new BN(100_000),
-        new BN(86400),
-        1
+        {
+          entryFee: new BN(1_990_000),
+          attackPackPrice: new BN(100_000),
+          durationSecs: new BN(86400),
+          entryHourlyIncPct: 1,
+          rerollFees: [new BN(1_000_000), new BN(1_000_000), new BN(1_000_000)],
+          maxPlayers: 0, // no cap
+          minPlayers: 0,
+          minPlayersDeadlineSecs: new BN(0),
+          claimWindowSecs: new BN(0), // claims never expire
+        },
+        null,
+        null,
+        { token: {} }
       )
       .accountsPartial({
-        admin: admin.publicKey,
+        operator: admin.publicKey,
         mint: mint,
         globalConfig: globalConfigPDA,
+        arena: arenaPDA,
+        roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
         gameRound: roundPDA,
+        leaderboard: getLeaderboardPDA(program, roundPDA),
         vault: vaultPDA,
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -135,11 +172,12 @@ describe("Player Instructions", () => {
 
     try {
       await program.methods
-        .requestJoinRound(clientSeed)
+        .requestJoinRound(clientSeed, [], 0) // public round: no proof, no discount
         .accountsPartial({
           player: player1.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          mint: mint,
           playerState: player1StatePDA,
           playerTokenAccount: player1TokenAccount,
           vault: vaultPDA,
@@ -170,6 +208,7 @@ describe("Player Instructions", () => {
           player: player1.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          mint: mint,
           playerState: player1StatePDA,
           playerTokenAccount: player1TokenAccount,
           vault: vaultPDA,
@@ -196,6 +235,7 @@ describe("Player Instructions", () => {
           player: player1.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          mint: mint,
           playerState: player1StatePDA,
           playerTokenAccount: player1TokenAccount,
           vault: vaultPDA,
@@ -236,12 +276,69 @@ describe("Player Instructions", () => {
     }
   });
 
+  it("Sponsor round", async () => {
+    const amount = new BN(5_000_000);
+    const sponsorPDA = getSponsorPDA(program, roundPDA, player1.publicKey);
+    const pendingDepositsPDA = getPendingDepositsPDA(program, roundPDA);
+    const before = await getGameRound(program, roundPDA);
+
+    await program.methods
+      .sponsorRound(new BN(1), amount)
+      .accountsPartial({
+        sponsor: player1.publicKey,
+        globalConfig: globalConfigPDA,
+        arena: arenaPDA,
+        gameRound: roundPDA,
+        pendingDeposits: pendingDepositsPDA,
+        sponsorRecord: sponsorPDA,
+        mint: mint,
+        sponsorTokenAccount: player1TokenAccount,
+        vault: vaultPDA,
+        solVault: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([player1])
+      .rpc();
+
+    // Sponsorship is recorded in full (no rake) and reaches the pool when the round
+    // settles; the round itself may be delegated, so it is not written here
+    const pending = await getPendingDeposits(program, pendingDepositsPDA);
+    assert.equal(pending.sponsored.toString(), amount.toString());
+    assert.equal(pending.sponsors, 1);
+    const gameRound = await getGameRound(program, roundPDA);
+    assert.equal(gameRound.prizePool.toString(), before.prizePool.toString());
+
+    const sponsor = await program.account.sponsor.fetch(sponsorPDA);
+    assert.equal(sponsor.sponsor.toString(), player1.publicKey.toString());
+    assert.equal(sponsor.amount.toString(), amount.toString());
+  });
+
+  it("Migrate player state checks the player's address", async () => {
+    // The seeds bind the player state to the round and wallet it was created for
+    try {
+      await program.methods
+        .migratePlayerState()
+        .accountsPartial({
+          payer: admin.publicKey,
+          gameRound: roundPDA,
+          player: player2.publicKey,
+          playerState: player1StatePDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Another player's state should not migrate");
+    } catch (err: any) {
+      assert.include(err.message, "ConstraintSeeds");
+    }
+  });
+
   it("Claim prize", async () => {
     // End round first
     await program.methods
       .endRound()
       .accountsPartial({
-        admin: admin.publicKey,
+        caller: admin.publicKey,
         globalConfig: globalConfigPDA,
         gameRound: roundPDA,
       })
@@ -254,6 +351,7 @@ describe("Player Instructions", () => {
           player: player1.publicKey,
           globalConfig: globalConfigPDA,
           gameRound: roundPDA,
+          mint: mint,
           playerState: player1StatePDA,
           playerTokenAccount: player1TokenAccount,
           vault: vaultPDA,
@@ -269,4 +367,495 @@ describe("Player Instructions", () => {
       console.log("Claim prize failed (payouts may not be processed):", err.message);
     }
   });
+
+  it("Claim refund after cancellation", async function () {
+    await program.methods
+      .cancelRound()
+      .accountsPartial({
+        operator: admin.publicKey,
+        globalConfig: globalConfigPDA,
+        gameRound: roundPDA,
+        pendingDeposits: getPendingDepositsPDA(program, roundPDA),
+      })
+      .rpc();
+
+    const gameRound = await getGameRound(program, roundPDA);
+    assert.deepEqual(gameRound.status, { cancelled: {} });
+    // The sponsorship recorded in the pending deposits is folded in at cancellation
+    const pending = await getPendingDeposits(
+      program,
+      getPendingDepositsPDA(program, roundPDA)
+    );
+    assert.equal(gameRound.sponsoredAmount.toString(), pending.sponsored.toString());
+    assert.equal(gameRound.openSponsors, pending.sponsors);
+    // Refunds come out of the pool as it stood at cancellation, never more than was paid in
+    const deposits = gameRound.playersPaid.add(gameRound.sponsoredAmount);
+    assert.equal(
+      gameRound.refundPool.toString(),
+      BN.min(gameRound.prizePool, deposits).toString()
+    );
+
+    if (!(await provider.connection.getAccountInfo(player1StatePDA))) {
+      console.log("Player never joined (VRF unavailable); skipping refund amount check");
+      this.skip();
+    }
+
+    // Pro-rata share of the refund pool, shared with sponsors
+    const playerState = await getPlayerState(program, player1StatePDA);
+    const totalPaid = playerState.entryFeePaid
+      .add(playerState.packsSpent)
+      .add(playerState.rerollsSpent);
+    const expectedRefund = totalPaid.mul(gameRound.refundPool).div(deposits);
+    const balanceBefore = await getAccount(provider.connection, player1TokenAccount);
+
+    await program.methods
+      .claimRefund()
+      .accountsPartial({
+        player: player1.publicKey,
+        globalConfig: globalConfigPDA,
+        gameRound: roundPDA,
+        mint: mint,
+        playerState: player1StatePDA,
+        playerTokenAccount: player1TokenAccount,
+        vault: vaultPDA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([player1])
+      .rpc();
+
+    const balanceAfter = await getAccount(provider.connection, player1TokenAccount);
+    assert.equal(
+      (balanceAfter.amount - balanceBefore.amount).toString(),
+      expectedRefund.toString()
+    );
+    const claimed = await getPlayerState(program, player1StatePDA);
+    assert.equal(claimed.prizeClaimed, true);
+  });
+
+  it("Claim sponsor refund after cancellation", async () => {
+    const sponsorPDA = getSponsorPDA(program, roundPDA, player1.publicKey);
+    const gameRound = await getGameRound(program, roundPDA);
+    const sponsor = await program.account.sponsor.fetch(sponsorPDA);
+
+    // Sponsors share the refund pool pro rata with players
+    const expectedRefund = sponsor.amount
+      .mul(gameRound.refundPool)
+      .div(gameRound.playersPaid.add(gameRound.sponsoredAmount));
+    const balanceBefore = await getAccount(provider.connection, player1TokenAccount);
+
+    await program.methods
+      .claimSponsorRefund()
+      .accountsPartial({
+        sponsor: player1.publicKey,
+        globalConfig: globalConfigPDA,
+        gameRound: roundPDA,
+        sponsorRecord: sponsorPDA,
+        mint: mint,
+        sponsorTokenAccount: player1TokenAccount,
+        vault: vaultPDA,
+        solVault: null,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([player1])
+      .rpc();
+
+    const balanceAfter = await getAccount(provider.connection, player1TokenAccount);
+    assert.equal(
+      (balanceAfter.amount - balanceBefore.amount).toString(),
+      expectedRefund.toString()
+    );
+
+    // The sponsor record is closed with the claim, so the round can close later
+    assert.isNull(await provider.connection.getAccountInfo(sponsorPDA));
+    const after = await getGameRound(program, roundPDA);
+    assert.equal(after.openSponsors, gameRound.openSponsors - 1);
+  });
+
+  describe("Arena mint change during a live round", () => {
+    let liveArenaPDA: PublicKey;
+    let liveRoundPDA: PublicKey;
+    let liveVaultPDA: PublicKey;
+    let newMint: PublicKey;
+
+    before(async () => {
+      liveArenaPDA = getArenaPDA(program, globalConfigPDA, 4);
+      await program.methods
+        .createArena(4, Array(32).fill(0))
+        .accountsPartial({
+          admin: admin.publicKey,
+          globalConfig: globalConfigPDA,
+          mint: mint,
+          arena: liveArenaPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      liveRoundPDA = getGameRoundPDA(program, liveArenaPDA, 1);
+      liveVaultPDA = await getAssociatedTokenAddress(mint, liveRoundPDA, true);
+      await program.methods
+        .createRound(
+          {
+            entryFee: new BN(1_990_000),
+            attackPackPrice: new BN(100_000),
+            durationSecs: new BN(86400),
+            entryHourlyIncPct: 1,
+            rerollFees: [new BN(1_000_000), new BN(1_000_000), new BN(1_000_000)],
+            maxPlayers: 0,
+            minPlayers: 0,
+            minPlayersDeadlineSecs: new BN(0),
+            claimWindowSecs: new BN(0),
+          },
+          null,
+          null,
+          { token: {} }
+        )
+        .accountsPartial({
+          operator: admin.publicKey,
+          mint: mint,
+          globalConfig: globalConfigPDA,
+          arena: liveArenaPDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
+          gameRound: liveRoundPDA,
+          leaderboard: getLeaderboardPDA(program, liveRoundPDA),
+          vault: liveVaultPDA,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // Switch the arena to another mint while the round is live
+      newMint = await createMint(provider.connection, admin.payer, admin.publicKey, null, 6);
+      await program.methods
+        .updateArena(null, null)
+        .accountsPartial({
+          admin: admin.publicKey,
+          globalConfig: globalConfigPDA,
+          arena: liveArenaPDA,
+          mint: newMint,
+        })
+        .rpc();
+    });
+
+    it("Keeps the live round on its own mint", async () => {
+      const arena = await getArena(program, liveArenaPDA);
+      assert.equal(arena.tokenMint.toString(), newMint.toString());
+
+      const gameRound = await getGameRound(program, liveRoundPDA);
+      assert.equal(gameRound.mint.toString(), mint.toString());
+
+      // Deposits in the round's mint still land in its pool
+      const amount = new BN(1_000_000);
+      await program.methods
+        .sponsorRound(new BN(1), amount)
+        .accountsPartial({
+          sponsor: player2.publicKey,
+          globalConfig: globalConfigPDA,
+          arena: liveArenaPDA,
+          gameRound: liveRoundPDA,
+          pendingDeposits: getPendingDepositsPDA(program, liveRoundPDA),
+          sponsorRecord: getSponsorPDA(program, liveRoundPDA, player2.publicKey),
+          mint: mint,
+          sponsorTokenAccount: player2TokenAccount,
+          vault: liveVaultPDA,
+          solVault: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([player2])
+        .rpc();
+
+      const pending = await getPendingDeposits(
+        program,
+        getPendingDepositsPDA(program, liveRoundPDA)
+      );
+      assert.equal(pending.sponsored.toString(), amount.toString());
+
+      // The arena's new mint is refused for the live round
+      try {
+        await program.methods
+          .sponsorRound(new BN(1), amount)
+          .accountsPartial({
+            sponsor: player2.publicKey,
+            globalConfig: globalConfigPDA,
+            arena: liveArenaPDA,
+            gameRound: liveRoundPDA,
+            pendingDeposits: getPendingDepositsPDA(program, liveRoundPDA),
+            sponsorRecord: getSponsorPDA(program, liveRoundPDA, player2.publicKey),
+            mint: newMint,
+            sponsorTokenAccount: player2TokenAccount,
+            vault: liveVaultPDA,
+            solVault: null,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([player2])
+          .rpc();
+        assert.fail("The arena's new mint should not be accepted by a live round");
+      } catch (err: any) {
+        assert.include(err.message, "InvalidMint");
+      }
+    });
+
+    it("Joins and refunds of the live round keep working", async function () {
+      const livePlayerStatePDA = getPlayerStatePDA(program, liveRoundPDA, player2.publicKey);
+
+      try {
+        await program.methods
+          .requestJoinRound(7, [], 0)
+          .accountsPartial({
+            player: player2.publicKey,
+            globalConfig: globalConfigPDA,
+            gameRound: liveRoundPDA,
+            mint: mint,
+            playerState: livePlayerStatePDA,
+            playerTokenAccount: player2TokenAccount,
+            vault: liveVaultPDA,
+            oracleQueue: ORACLE_QUEUE,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([player2])
+          .rpc();
+      } catch (err: any) {
+        console.log("Join round failed (VRF may not be available):", err.message);
+        this.skip();
+      }
+
+      const joined = await getPlayerState(program, livePlayerStatePDA);
+      assert.isTrue(joined.entryFeePaid.gtn(0));
+
+      await program.methods
+        .cancelRound()
+        .accountsPartial({
+          operator: admin.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: liveRoundPDA,
+          pendingDeposits: getPendingDepositsPDA(program, liveRoundPDA),
+        })
+        .rpc();
+
+      const gameRound = await getGameRound(program, liveRoundPDA);
+      const expectedRefund = joined.entryFeePaid
+        .mul(gameRound.refundPool)
+        .div(gameRound.playersPaid.add(gameRound.sponsoredAmount));
+      const balanceBefore = await getAccount(provider.connection, player2TokenAccount);
+
+      await program.methods
+        .claimRefund()
+        .accountsPartial({
+          player: player2.publicKey,
+          globalConfig: globalConfigPDA,
+          gameRound: liveRoundPDA,
+          mint: mint,
+          playerState: livePlayerStatePDA,
+          playerTokenAccount: player2TokenAccount,
+          vault: liveVaultPDA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([player2])
+        .rpc();
+
+      const balanceAfter = await getAccount(provider.connection, player2TokenAccount);
+      assert.equal(
+        (balanceAfter.amount - balanceBefore.amount).toString(),
+        expectedRefund.toString()
+      );
+    });
+
+    it("Keeps the sponsor record open while its refund is claimable", async () => {
+      try {
+        await program.methods
+          .closeSponsor()
+          .accountsPartial({
+            caller: admin.publicKey,
+            globalConfig: globalConfigPDA,
+            gameRound: liveRoundPDA,
+            sponsorRecord: getSponsorPDA(program, liveRoundPDA, player2.publicKey),
+            sponsor: player2.publicKey,
+          })
+          .rpc();
+        assert.fail("An unclaimed sponsor refund should keep the record open");
+      } catch (err: any) {
+        // RoundNotSettled if the round was not cancelled above (no VRF join)
+        assert.match(err.message, /UnclaimedRewards|RoundNotSettled/);
+      }
+    });
+  });
+
+  describe("Token-2022 transfer-fee mint", () => {
+    const FEE_BPS = 100; // 1% withheld on every transfer
+
+    let feeMint: PublicKey;
+    let feeArenaPDA: PublicKey;
+    let feeRoundPDA: PublicKey;
+    let feeVaultPDA: PublicKey;
+    let payerTokenAccount: PublicKey;
+
+    before(async () => {
+      feeMint = await createTransferFeeMint(
+        provider.connection,
+        admin.payer,
+        FEE_BPS,
+        BigInt(1_000_000_000)
+      );
+      payerTokenAccount = await createAssociatedTokenAccount(
+        provider.connection,
+        admin.payer,
+        feeMint,
+        player2.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await mintTo(
+        provider.connection,
+        admin.payer,
+        feeMint,
+        payerTokenAccount,
+        admin.payer,
+        100_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+
+      feeArenaPDA = getArenaPDA(program, globalConfigPDA, 5);
+      await program.methods
+        .createArena(5, Array(32).fill(0))
+        .accountsPartial({
+          admin: admin.publicKey,
+          globalConfig: globalConfigPDA,
+          mint: feeMint,
+          arena: feeArenaPDA,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      feeRoundPDA = getGameRoundPDA(program, feeArenaPDA, 1);
+      feeVaultPDA = await getAssociatedTokenAddress(
+        feeMint,
+        feeRoundPDA,
+        true,
+        TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .createRound(
+          {
+            entryFee: new BN(1_990_000),
+            attackPackPrice: new BN(100_000),
+            durationSecs: new BN(86400),
+            entryHourlyIncPct: 1,
+            rerollFees: [new BN(1_000_000), new BN(1_000_000), new BN(1_000_000)],
+            maxPlayers: 0,
+            minPlayers: 0,
+            minPlayersDeadlineSecs: new BN(0),
+            claimWindowSecs: new BN(0),
+          },
+          null,
+          null,
+          { token: {} }
+        )
+        .accountsPartial({
+          operator: admin.publicKey,
+          mint: feeMint,
+          globalConfig: globalConfigPDA,
+          arena: feeArenaPDA,
+          roundRules: getRoundRulesPDA(program, globalConfigPDA, 1),
+          gameRound: feeRoundPDA,
+          leaderboard: getLeaderboardPDA(program, feeRoundPDA),
+          vault: feeVaultPDA,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+    });
+
+    async function vaultBalance(): Promise<bigint> {
+      const vault = await getAccount(
+        provider.connection,
+        feeVaultPDA,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      );
+      return vault.amount;
+    }
+
+    it("Credits sponsorships net of the transfer fee", async () => {
+      const amount = new BN(10_000_000);
+      const sponsorPDA = getSponsorPDA(program, feeRoundPDA, player2.publicKey);
+      const pendingDepositsPDA = getPendingDepositsPDA(program, feeRoundPDA);
+      const vaultBefore = await vaultBalance();
+
+      await program.methods
+        .sponsorRound(new BN(1), amount)
+        .accountsPartial({
+          sponsor: player2.publicKey,
+          globalConfig: globalConfigPDA,
+          arena: feeArenaPDA,
+          gameRound: feeRoundPDA,
+          pendingDeposits: pendingDepositsPDA,
+          sponsorRecord: sponsorPDA,
+          mint: feeMint,
+          sponsorTokenAccount: payerTokenAccount,
+          vault: feeVaultPDA,
+          solVault: null,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([player2])
+        .rpc();
+
+      // The mint withholds its fee, so the vault gets 99% of what was sent
+      const received = (await vaultBalance()) - vaultBefore;
+      const expected = amount.muln(10_000 - FEE_BPS).divn(10_000);
+      assert.equal(received.toString(), expected.toString());
+
+      const pending = await getPendingDeposits(program, pendingDepositsPDA);
+      assert.equal(pending.sponsored.toString(), received.toString());
+      const sponsor = await program.account.sponsor.fetch(sponsorPDA);
+      assert.equal(sponsor.amount.toString(), received.toString());
+    });
+
+    it("Credits entry fees net of the transfer fee", async function () {
+      const feePlayerStatePDA = getPlayerStatePDA(program, feeRoundPDA, player2.publicKey);
+      const roundBefore = await getGameRound(program, feeRoundPDA);
+      const vaultBefore = await vaultBalance();
+
+      try {
+        await program.methods
+          .requestJoinRound(9, [], 0)
+          .accountsPartial({
+            player: player2.publicKey,
+            globalConfig: globalConfigPDA,
+            gameRound: feeRoundPDA,
+            mint: feeMint,
+            playerState: feePlayerStatePDA,
+            playerTokenAccount: payerTokenAccount,
+            vault: feeVaultPDA,
+            oracleQueue: ORACLE_QUEUE,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([player2])
+          .rpc();
+      } catch (err: any) {
+        console.log("Join round failed (VRF may not be available):", err.message);
+        this.skip();
+      }
+
+      const received = (await vaultBalance()) - vaultBefore;
+      const playerState = await getPlayerState(program, feePlayerStatePDA);
+      assert.equal(playerState.entryFeePaid.toString(), received.toString());
+      assert.isTrue(playerState.entryFeePaid.lt(roundBefore.entryFee));
+
+      // Pool and rake together account for exactly what the vault received
+      const roundAfter = await getGameRound(program, feeRoundPDA);
+      const credited = roundAfter.prizePool
+        .sub(roundBefore.prizePool)
+        .add(roundAfter.protocolFees.sub(roundBefore.protocolFees));
+      assert.equal(credited.toString(), received.toString());
+    });
+  });
 });
@@ -1,7 +1,25 @@
 import { Program, BN } from "@coral-xyz/anchor";
 import { PandaBattle } from "../target/types/panda_battle";
-import { PublicKey, Connection, LAMPORTS_PER_SOL } from "@solana/web3.js";
-import { getAssociatedTokenAddress, mintTo, createMint } from "@solana/spl-token";
+import {
+  PublicKey,
+  Connection,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  SystemProgram,
+  Transaction,
+  sendAndConfirmTransaction,
+} from "@solana/web3.js";
+import {
+  getAssociatedTokenAddress,
+  mintTo,
+  createMint,
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  getMintLen,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeMintInstruction,
+} from "@solana/spl-token";
+import { createHash } from "crypto";
 
 /**
  * Airdrop SOL to an account
@@ -29,18 +47,37 @@ export function getGlobalConfigPDA(program: Program<PandaBattle>): PublicKey {
   return pda;
 }
 
+/**
+ * Get Arena PDA
+ */
+export function getArenaPDA(
+  program: Program<PandaBattle>,
+  globalConfigPDA: PublicKey,
+  arenaId: number
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("arena"),
+      globalConfigPDA.toBuffer(),
+      new BN(arenaId).toArrayLike(Buffer, "le", 4),
+    ],
+    program.programId
+  );
+  return pda;
+}
+
 /**
  * Get Game Round PDA
  */
 export function getGameRoundPDA(
   program: Program<PandaBattle>,
-  globalConfigPDA: PublicKey,
+  arenaPDA: PublicKey,
   roundNumber: number
 ): PublicKey {
   const [pda] = PublicKey.findProgramAddressSync(
     [
       Buffer.from("game_round"),
-      globalConfigPDA.toBuffer(),
+      arenaPDA.toBuffer(),
       new BN(roundNumber).toArrayLike(Buffer, "le", 8),
     ],
     program.programId
@@ -48,6 +85,103 @@ export function getGameRoundPDA(
   return pda;
 }
 
+/**
+ * Get Round Template PDA
+ */
+export function getRoundTemplatePDA(
+  program: Program<PandaBattle>,
+  globalConfigPDA: PublicKey,
+  templateId: number
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("round_template"),
+      globalConfigPDA.toBuffer(),
+      new BN(templateId).toArrayLike(Buffer, "le", 4),
+    ],
+    program.programId
+  );
+  return pda;
+}
+
+/**
+ * Get Rollover Vault PDA
+ */
+export function getRolloverVaultPDA(
+  program: Program<PandaBattle>,
+  globalConfigPDA: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("rollover_vault"), globalConfigPDA.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+/**
+ * Get the lamport vault PDA of a native SOL round
+ */
+export function getSolVaultPDA(
+  program: Program<PandaBattle>,
+  gameRoundPDA: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("sol_vault"), gameRoundPDA.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+/**
+ * Get Sponsor PDA
+ */
+export function getSponsorPDA(
+  program: Program<PandaBattle>,
+  gameRoundPDA: PublicKey,
+  sponsor: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("sponsor"), gameRoundPDA.toBuffer(), sponsor.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
+/**
+ * Get Round Rules PDA
+ */
+export function getRoundRulesPDA(
+  program: Program<PandaBattle>,
+  globalConfigPDA: PublicKey,
+  rulesId: number
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("round_rules"),
+      globalConfigPDA.toBuffer(),
+      new BN(rulesId).toArrayLike(Buffer, "le", 4),
+    ],
+    program.programId
+  );
+  return pda;
+}
+
+/**
+ * Default round rules (the original hardcoded game tuning)
+ */
+export const DEFAULT_ROUND_RULES = {
+  maxTurns: 50,
+  startingTurns: 3,
+  turnsPerHour: 3,
+  levelXpThresholds: [0, 5, 15, 30, 50, 75, 105, 140, 180, 225, 275],
+  xpPerWin: 3,
+  maxAttribute: 20,
+  maxBattleTurns: 10,
+  critDamageMultiplier: 5000, // +50% in basis points
+  topPoolPct: 80,
+  leaderboardSize: 20,
+};
+
 /**
  * Get Player State PDA
  */
@@ -63,6 +197,20 @@ export function getPlayerStatePDA(
   return pda;
 }
 
+/**
+ * Get PendingDeposits PDA
+ */
+export function getPendingDepositsPDA(
+  program: Program<PandaBattle>,
+  gameRoundPDA: PublicKey
+): PublicKey {
+  const [pda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pending_deposits"), gameRoundPDA.toBuffer()],
+    program.programId
+  );
+  return pda;
+}
+
 /**
  * Get Leaderboard PDA
  */
@@ -87,6 +235,16 @@ export async function getGlobalConfig(
   return await program.account.globalConfig.fetch(globalConfigPDA);
 }
 
+/**
+ * Fetch arena account
+ */
+export async function getArena(
+  program: Program<PandaBattle>,
+  arenaPDA: PublicKey
+) {
+  return await program.account.arena.fetch(arenaPDA);
+}
+
 /**
  * Fetch game round account
  */
@@ -97,6 +255,26 @@ export async function getGameRound(
   return await program.account.gameRound.fetch(roundPDA);
 }
 
+/**
+ * Fetch round rules account
+ */
+export async function getRoundRules(
+  program: Program<PandaBattle>,
+  roundRulesPDA: PublicKey
+) {
+  return await program.account.roundRules.fetch(roundRulesPDA);
+}
+
+/**
+ * Fetch round template account
+ */
+export async function getRoundTemplate(
+  program: Program<PandaBattle>,
+  roundTemplatePDA: PublicKey
+) {
+  return await program.account.roundTemplate.fetch(roundTemplatePDA);
+}
+
 /**
  * Fetch player state account
  */
@@ -117,6 +295,16 @@ export async function getLeaderboard(
   return await program.account.leaderboard.fetch(leaderboardPDA);
 }
 
+/**
+ * Fetch pending deposits account
+ */
+export async function getPendingDeposits(
+  program: Program<PandaBattle>,
+  pendingDepositsPDA: PublicKey
+) {
+  return await program.account.pendingDeposits.fetch(pendingDepositsPDA);
+}
+
 /**
  * Wait for specified seconds (for time-based tests)
  */
@@ -150,3 +338,95 @@ export async function setupTestToken(
 
   return mint;
 }
+
+/**
+ * Create a Token-2022 mint with a transfer-fee extension (payer is the mint authority)
+ */
+export async function createTransferFeeMint(
+  connection: Connection,
+  payer: Keypair,
+  feeBps: number,
+  maxFee: bigint,
+  decimals: number = 6
+): Promise<PublicKey> {
+  const mintKeypair = Keypair.generate();
+  const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+  const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
+
+  const tx = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: payer.publicKey,
+      newAccountPubkey: mintKeypair.publicKey,
+      space: mintLen,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    createInitializeTransferFeeConfigInstruction(
+      mintKeypair.publicKey,
+      payer.publicKey,
+      payer.publicKey,
+      feeBps,
+      maxFee,
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createInitializeMintInstruction(
+      mintKeypair.publicKey,
+      decimals,
+      payer.publicKey,
+      null,
+      TOKEN_2022_PROGRAM_ID
+    )
+  );
+  await sendAndConfirmTransaction(connection, tx, [payer, mintKeypair]);
+
+  return mintKeypair.publicKey;
+}
+
+/**
+ * Allowlist Merkle leaf: sha256(0x00 || wallet || discount_bps u16 LE)
+ */
+export function getAllowlistLeaf(wallet: PublicKey, discountBps: number): Buffer {
+  const discount = Buffer.alloc(2);
+  discount.writeUInt16LE(discountBps);
+  return createHash("sha256")
+    .update(Buffer.from([0]))
+    .update(wallet.toBuffer())
+    .update(discount)
+    .digest();
+}
+
+function hashAllowlistNode(a: Buffer, b: Buffer): Buffer {
+  const [left, right] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return createHash("sha256")
+    .update(Buffer.from([1]))
+    .update(left)
+    .update(right)
+    .digest();
+}
+
+/**
+ * Build the allowlist Merkle root and a proof per entry (same order as entries)
+ */
+export function buildAllowlist(
+  entries: { wallet: PublicKey; discountBps: number }[]
+): { root: Buffer; proofs: Buffer[][] } {
+  let level = entries.map((e) => getAllowlistLeaf(e.wallet, e.discountBps));
+  let positions = entries.map((_, i) => i);
+  const proofs: Buffer[][] = entries.map(() => []);
+
+  while (level.length > 1) {
+    const next: Buffer[] = [];
+    for (let i = 0; i < level.length; i += 2) {
+      // An unpaired node is promoted unchanged
+      next.push(i + 1 < level.length ? hashAllowlistNode(level[i], level[i + 1]) : level[i]);
+    }
+    positions = positions.map((pos, leaf) => {
+      const sibling = pos ^ 1;
+      if (sibling < level.length) proofs[leaf].push(level[sibling]);
+      return pos >> 1;
+    });
+    level = next;
+  }
+
+  return { root: level[0], proofs };
+}